@@ -35,9 +35,9 @@ fn generate_manchester_diagram() -> Diagram {
 
     let mut diagram = Diagram::new(Some("Manchester Encoding".to_string()));
 
-    let lane1 = Lane::new(clk).add_marker(markerline).clone();
-    let mut lane2 = Lane::new(s1).add_marker(markerline).clone();
-    let mut lane3 = Lane::new(s2).add_marker(markerline).clone();
+    let lane1 = Lane::new(clk).add_marker(markerline.clone()).clone();
+    let mut lane2 = Lane::new(s1).add_marker(markerline.clone()).clone();
+    let mut lane3 = Lane::new(s2).add_marker(markerline.clone()).clone();
         
     lane2.labels.extend(labels.clone());
     lane3.labels.extend(labels);
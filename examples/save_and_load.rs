@@ -0,0 +1,21 @@
+use siggi::{compose::Compositor, model::{Diagram, utils::Color, signal::{Clock, SignalGenerator}, Signal, marker::Marker, Lane}, parse};
+
+fn main() {
+    let clk = Clock::positiv(4).to_signal().color_with(Color::Yellow);
+    let data = Signal::new("Data", "lhhl".parse().unwrap()).shift(0.5).color_with(Color::Blue);
+
+    let diagram = Diagram::new(Some("Saved diagram".to_string()))
+        .add(Lane::new(clk).add_marker(Marker::new(1.5, true, 1.2, Color::Red)))
+        .add(Lane::new(data).add_label_at("valid", 2.0));
+
+    // Save the diagram model as json ...
+    let json = parse::to_json_string(&diagram).expect("Could not save the diagram.");
+    println!("{}", json);
+
+    // ... and load it again.
+    let loaded = parse::from_json_str(&json).expect("Could not load the diagram.");
+    assert_eq!(json, parse::to_json_string(&loaded).unwrap());
+
+    let doc = Compositor::default().compose(&loaded);
+    svg::save("saved.svg", &doc).expect("Could not save the diagram.");
+}
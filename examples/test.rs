@@ -27,12 +27,12 @@ fn generate_test_diagram() -> Diagram {
     let markerline = marker::Marker::new(1.5,true,1.2, Color::Red);
     d1.append(
         Lane::new(nclk)
-            .add_marker(markerline)
+            .add_marker(markerline.clone())
             .add_label_at("rising edge", 1.5)
         );
     d1.append(
         Lane::new(s1)
-            .add_marker(markerline)
+            .add_marker(markerline.clone())
             .add_label_at("0", 0.5)
             .add_label_at("1", 1.5)
             .add_label_at("0", 2.5)
@@ -1,201 +1,1207 @@
 // compose/mod.rs
-use svg::{self, node::element::{Group, Text, Path, Rectangle, Line, path::Data, Definitions, Marker, Polygon}, Node};
-
-use crate::model::{Diagram, marker::{Label, TextAnchor, Marker as LineMarker, Positionable}, Lane, utils::Color, Signal, signal::Level};
-
-// Constants
-const PADDING: f64 = 30.0;
-const _PADDING_TOP: f64 = PADDING;
-const _PADDING_BOTTOM: f64 = PADDING;
-const _PADDING_LEFT: f64 = PADDING;
-const _PADDING_RIGHT: f64 = PADDING;
-const HEADER_HEIGHT: f64 = 25.0;  // if no title set to 0;
-const FOOTER_HEIGHT: f64 = 0.0;
-
-const WAVE_PERIOD_WIDTH: f64 = 50.0;
-const WAVE_HEIGHT: f64 = WAVE_PERIOD_WIDTH;
-const WAVE_PADDING: f64 = WAVE_HEIGHT/2.0;
-const WAVE_PADDING_TOP: f64 = WAVE_PADDING;
-const WAVE_PADDING_BOTTOM: f64 = WAVE_PADDING;
-const LANE_HEIGHT: f64 = WAVE_HEIGHT + WAVE_PADDING_TOP + WAVE_PADDING_BOTTOM;
-
-const DEFAULT_WAVE_OFFSET: f64 = WAVE_PERIOD_WIDTH * 1.5;
-#[allow(unused)]
-const MIN_WAVE_WIDTH: f64 = WAVE_PERIOD_WIDTH * 4.0;
-#[allow(unused)]
-const MIN_LANE_WIDTH: f64 = DEFAULT_WAVE_OFFSET + MIN_WAVE_WIDTH;
-
-#[derive(Debug, Clone, Copy, Default)]
+pub mod font;
+pub mod text;
+pub mod tikz;
+pub mod vcd;
+
+use std::{borrow::Cow, cell::RefCell, collections::BTreeSet};
+
+use svg::{self, node::element::{Group, Text, Path, Rectangle, Line, path::Data, Definitions, Marker, Polygon, ClipPath, Animate, AnimateTransform, Style as StyleSheet}, Node};
+
+use crate::model::{Diagram, marker::{Label, TextAnchor, TextSize, Marker as LineMarker, Positionable}, Lane, utils::Color, Signal, signal::Level, Style, style::Grid, Theme, axis, edge::EdgeLine, Dimension, dimension::Side, Region, legend::{LegendEntry, LegendPosition, Swatch}};
+
+// Space between the text columns and the waves.
+const NAME_GAP: f64 = 15.0;
+const Y_AXIS_GAP: f64 = 5.0;
+// Other fonts are estimated with the metrics of the bundled font, handwriting fonts tend to be wider.
+const FONT_WIDTH_MARGIN: f64 = 1.3;
+// Length of the ticks of the time axis.
+const MAJOR_TICK_LENGTH: f64 = 6.0;
+const MINOR_TICK_LENGTH: f64 = 3.0;
+// Length and half width of the arrow heads of dimension lines.
+const DIMENSION_ARROW_LENGTH: f64 = 6.0;
+const DIMENSION_ARROW_WIDTH: f64 = 2.5;
+// Distance of the x-axis arrow from the lanes.
+const XAXIS_Y: f64 = 10.0;
+// Space between the diagram and the caption.
+const FOOTER_GAP: f64 = 10.0;
+// Space between the waves and a legend right of them, also used between the entries of a legend below.
+const LEGEND_GAP: f64 = 20.0;
+// Width of a legend entry's swatch and the space to its description.
+const SWATCH_WIDTH: f64 = 24.0;
+const SWATCH_GAP: f64 = 6.0;
+// Distance between the baselines of two lines of text relative to the font size.
+const LINE_SPACING: f64 = 1.4;
+// Space between the rows of a wrapped diagram.
+const ROW_GAP: f64 = 20.0;
+// Length of the bracket's ends pointing to the lanes of a group.
+const BRACKET_WIDTH: f64 = 4.0;
+
+/// A backend turns a diagram into a specific output format.
+pub trait Backend {
+    type Output;
+
+    fn render(&self, diag: &Diagram) -> Self::Output;
+}
+
+// Placement of a dimension line in px from the time 0, its side (0 above, 1 below the wave) and its row counted from the wave.
+struct DimensionLayout {
+    label: Label,
+    x1: f64,
+    x2: f64,
+    // the arrows and the label fit between the ends.
+    inside: bool,
+    side: usize,
+    row: usize,
+}
+
+/// Describes where the waves are placed inside a composed document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaveGeometry {
+    /// The rows of a wrapped diagram, other diagrams have a single row.
+    pub rows: Vec<RowGeometry>,
+    /// Offset of each lane's top from the row's top, lanes with stacked dimension lines take more space.
+    pub lane_tops: Vec<f64>,
+    pub period_width: f64,
+    /// Offset of the wave's high level from the lane's top.
+    pub wave_top: f64,
+    pub wave_height: f64,
+    pub lane_height: f64,
+}
+
+/// Describes where a row of lanes is placed inside a composed document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowGeometry {
+    /// x coordinate of the time 0, which lies left of the waves for later rows.
+    pub x: f64,
+    /// y coordinate of the top of the row's lanes.
+    pub y: f64,
+    /// First time shown by the row in periods.
+    pub start: f64,
+    /// Last time shown by the row in periods.
+    pub end: f64,
+    /// Prefix of the ids of the row's elements, e.g. "row-1-" for the lane "row-1-lane-0".
+    pub id_prefix: String,
+}
+
+/// Animation which reveals the waves of all lanes from left to right.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Animation {
+    /// Time in seconds to reveal the whole diagram.
+    pub duration: f64,
+    /// Restart the animation after it finished.
+    pub repeat: bool,
+    /// Show a time cursor moving along with the revealed waves.
+    pub cursor: bool,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self { duration: 5.0, repeat: true, cursor: false }
+    }
+}
+
+/// How texts are written into the composed documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextMode {
+    /// Plain text elements, which are rendered with the fonts installed on the viewing system.
+    #[default]
+    Font,
+    /// Text elements using a subset of the bundled font, which gets embedded as `@font-face`.
+    Embedded,
+    /// The outlines of the bundled font as paths, which don't depend on any font support.
+    Paths,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct Compositor{
-    config: Option<&'static str>
+    style: Option<Style>,
+    animation: Option<Animation>,
+    text_mode: TextMode,
+    wrap: Option<u32>,
+    zoom: Option<(f64, f64)>,
+    cut_marks: bool,
+    split_title: bool,
+    // Number of periods composed, which overrides the length of the diagram's waves.
+    // Splits use it to keep the length of the whole diagram.
+    length: Option<f64>,
+    // Characters of the texts composed in the embedded text mode, the embedded font keeps only their glyphs.
+    used_chars: RefCell<BTreeSet<char>>,
+}
+
+// A time range of the diagram, which is composed as one row.
+// Elements are placed in time coordinates shifted by the window's start. Only clipped windows
+// cut off the waves and skip the annotations outside of the range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Window {
+    start: f64,
+    end: f64,
+    clip: bool,
+    // the diagram continues before the start or after the end, which is shown by cut marks.
+    cut_start: bool,
+    cut_end: bool,
+    // the window ends the composed range, so the times at its end belong to it and not to a next window.
+    closed: bool,
+    // number of the row in a wrapped diagram, which prefixes the ids of the row's elements.
+    row: Option<usize>,
+}
+
+impl Window {
+    fn width(&self, style: &Style) -> f64 {
+        (self.end - self.start) * style.period_width
+    }
+
+    // x offset of the time 0 from the window's left edge.
+    fn origin(&self, style: &Style) -> f64 {
+        -self.start * style.period_width
+    }
+
+    // Check if the time lies inside the window, including its edges.
+    fn contains(&self, time: f64) -> bool {
+        !self.clip || (self.start - 1e-9 <= time && time <= self.end + 1e-9)
+    }
+
+    // Check if an element at the time belongs to the window. Elements on the edge between two windows go to the later one,
+    // so they are drawn only once.
+    fn holds(&self, time: f64) -> bool {
+        !self.clip || (self.start - 1e-9 <= time && (time < self.end - 1e-9 || (self.closed && time <= self.end + 1e-9)))
+    }
+
+    // Id of an element inside the window, unique across the rows of a wrapped diagram.
+    fn id<T: std::fmt::Display>(&self, name: T) -> String {
+        match self.row {
+            Some(row) => format!("row-{}-{}", row, name),
+            None => name.to_string(),
+        }
+    }
+
+    // Clip the range in periods to the window, returns None if they don't overlap.
+    fn overlap(&self, start: f64, end: f64) -> Option<(f64, f64)> {
+        if !self.clip {
+            return Some((start, end));
+        }
+        let (start, end) = (start.max(self.start), end.min(self.end));
+        (start < end || (start == end && self.contains(start))).then_some((start, end))
+    }
 }
 
 impl Compositor {
 
     pub fn new() -> Self { Self::default() }
-    pub fn with(&mut self, config: &'static str) -> &mut Self {
-        self.config = Some(config);
+
+    /// Compose all diagrams with the given style, overriding the diagram's own style.
+    pub fn style(&mut self, style: Style) -> &mut Self {
+        self.style = Some(style);
         self
     }
 
-    pub fn compose_splits(&self, diag: &Diagram) -> Vec<svg::Document> {     
-        let max_wave_width = get_max_wave_len(diag) as f64 * WAVE_PERIOD_WIDTH;
-        let mut result = Vec::<svg::Document>::new();
-        
-        for (num,lane) in diag.lanes().iter().enumerate() {
-            result.push(
-                svg::Document::new()
-                    .set("viewBox", (0,0,DEFAULT_WAVE_OFFSET + max_wave_width + PADDING,LANE_HEIGHT + PADDING))
-                    .add(self.compose_lane(num, lane, max_wave_width)
-                )
-            );
+    /// Animate the composed documents.
+    pub fn animate(&mut self, animation: Animation) -> &mut Self {
+        self.animation = Some(animation);
+        self
+    }
+
+    /// Set how texts are written into the composed documents.
+    pub fn text_mode(&mut self, mode: TextMode) -> &mut Self {
+        self.text_mode = mode;
+        self
+    }
+
+    /// Break diagrams longer than the given number of periods into several rows.
+    /// Each row repeats the signal names, 0 disables the wrapping.
+    pub fn wrap(&mut self, periods: u32) -> &mut Self {
+        self.wrap = (periods > 0).then_some(periods);
+        self
+    }
+
+    /// Only compose the time range from `from` to `to` (in periods), cutting off everything outside of it.
+    /// Wrapped diagrams break the range into rows.
+    pub fn zoom(&mut self, from: f64, to: f64) -> &mut Self {
+        self.zoom = Some((from.min(to), from.max(to)));
+        self
+    }
+
+    /// Show "…" at the edges of a zoomed range, where the diagram continues.
+    pub fn cut_marks(&mut self, cut_marks: bool) -> &mut Self {
+        self.cut_marks = cut_marks;
+        self
+    }
+
+    // The time ranges composed as rows.
+    fn windows(&self, diag: &Diagram) -> Vec<Window> {
+        let len = self.length(diag);
+        let (start, end) = match self.zoom {
+            Some((from, to)) => (from.clamp(0.0, len), to.clamp(0.0, len)),
+            None => (0.0, len),
+        };
+        let clip = self.zoom.is_some();
+        let window = |start: f64, end: f64, clip: bool| Window {
+            start, end, clip,
+            cut_start: self.cut_marks && start > 0.0,
+            cut_end: self.cut_marks && end < len,
+            closed: true,
+            row: None,
+        };
+        match self.wrap {
+            Some(periods) if end - start > periods as f64 => {
+                let rows = ((end - start) / periods as f64).ceil() as usize;
+                (0..rows).map(|row| {
+                    let row_start = start + (row as u32 * periods) as f64;
+                    let row_end = (row_start + periods as f64).min(end);
+                    let mut window = window(row_start, row_end, true);
+                    // only the edges of the whole range get cut marks.
+                    window.cut_start &= row == 0;
+                    window.cut_end &= row + 1 == rows;
+                    window.closed = row + 1 == rows;
+                    window.row = Some(row);
+                    window
+                }).collect()
+            }
+            _ => vec![window(start, end, clip)],
         }
-        result
     }
 
-    pub fn compose(&self, diag: &Diagram) -> svg::Document {
-        // calc document width an height
-        let max_wave_width = get_max_wave_len(diag) as f64 * WAVE_PERIOD_WIDTH;
-        let width = max_wave_width + DEFAULT_WAVE_OFFSET + PADDING * 2.0;  // todo!() calc correct wave offset.
-        let height = diag.lane_count() as f64 * LANE_HEIGHT + HEADER_HEIGHT + FOOTER_HEIGHT + PADDING * 2.0;
-        
-        println!("Document size is {}x{}",width,height);
-        println!("Longest wave found is {} periods", get_max_wave_len(diag));
+    // The compositor's style is used first, followed by the diagram's style and the default style.
+    // The name column grows to fit the widest signal name and y-axis label.
+    fn resolve_style<'a>(&'a self, diag: &'a Diagram) -> Cow<'a, Style> {
+        let mut style = self.style.as_ref().or(diag.style()).map(Cow::Borrowed).unwrap_or_default();
+        let name_width = self.name_width(diag, &style);
+        if name_width > style.name_width {
+            style.to_mut().name_width = name_width;
+        }
+        style
+    }
 
+    // Width needed left of the waves by the signal names and y-axis labels, see `compose_lane`.
+    fn name_width(&self, diag: &Diagram, style: &Style) -> f64 {
+        let normal = font::font_size(TextSize::Normal) * style.font_scale;
+        let small = font::font_size(TextSize::Small) * style.font_scale;
+        diag.lanes().iter().map(|lane| {
+            let name = self.text_width(&lane.signal.name, normal) + NAME_GAP;
+            let y_axis = self.text_width(&lane.signal.y_axis.0, small).max(self.text_width(&lane.signal.y_axis.1, small)) + Y_AXIS_GAP;
+            name.max(y_axis)
+        }).fold(0.0, f64::max)
+    }
 
-        let bg = Rectangle::new()
-            .set("id", "background")
-            .set("fill", diag.background().to_string())
-            .set("stroke", "none")
-            .set("width", "100%")
-            .set("height", "100%");
+    // Width of the column left of the names, which holds the brackets of the lane groups.
+    fn groups_width(&self, diag: &Diagram, style: &Style) -> f64 {
+        let depth = (0..diag.groups().len()).map(|idx| diag.group_depth(idx) + 1).max().unwrap_or(0);
+        depth as f64 * style.group_width
+    }
 
-        let title = Label::from(diag.title());
-        let title = Text::from(&title).translate(width/2.0,PADDING);
+    // Width of a text, which is only estimated if the text isn't set in the bundled font.
+    fn text_width(&self, text: &str, size: f64) -> f64 {
+        let width = font::text_width(text, size);
+        match self.text_mode {
+            TextMode::Font => width * FONT_WIDTH_MARGIN,
+            _ => width,
+        }
+    }
 
-        let mut lanes = Group::new().set("id", "lanes");
+    /// Show the diagram's title above each lane composed by `compose_splits`.
+    pub fn split_title(&mut self, split_title: bool) -> &mut Self {
+        self.split_title = split_title;
+        self
+    }
 
-        for (num,lane) in diag.lanes().iter().enumerate() {
-            lanes.append(self.compose_lane(num, lane, max_wave_width).translate(0.0, num as f64 * LANE_HEIGHT));
+    // Number of periods of the composed diagram.
+    fn length(&self, diag: &Diagram) -> f64 {
+        self.length.unwrap_or(get_max_wave_len(diag) as f64)
+    }
+
+    /// Compose each lane into a separate document, which is named after the lane's signal.
+    /// The lanes keep the diagram's style, theme and length, names are made unique and usable as file names.
+    pub fn compose_splits(&self, diag: &Diagram) -> Vec<(String, svg::Document)> {
+        let mut comp = self.clone();
+        comp.length = Some(self.length(diag));
+        // the style is resolved per lane, so the name column fits the lane's own name.
+        diag.split_lanes(self.split_title).into_iter()
+            .map(|(name, lane)| (name, comp.compose(&lane)))
+            .collect()
+    }
+
+    /// Get the position of the waves inside the document composed for the diagram.
+    pub fn geometry(&self, diag: &Diagram) -> WaveGeometry {
+        let style = self.resolve_style(diag);
+        let x = style.padding + self.groups_width(diag, &style) + style.name_width;
+        let top = style.padding + self.header_height(diag, &style);
+        let row_distance = self.row_distance(diag, &style);
+        WaveGeometry {
+            rows: self.windows(diag).iter().enumerate().map(|(row, window)| RowGeometry {
+                x: x + window.origin(&style),
+                y: top + row as f64 * row_distance,
+                start: window.start,
+                end: window.end,
+                id_prefix: window.id(""),
+            }).collect(),
+            lane_tops: self.lane_tops(diag, &style).0,
+            period_width: style.period_width,
+            wave_top: style.wave_padding,
+            wave_height: style.wave_height,
+            lane_height: style.lane_height(),
         }
+    }
+
+    // Distance between the tops of two rows of a wrapped diagram, which are separated by the time axis and a gap.
+    fn row_distance(&self, diag: &Diagram, style: &Style) -> f64 {
+        let ruler_height = if diag.time_axis().is_some() { self.time_axis_height(style) } else { 0.0 };
+        self.lane_tops(diag, style).1 + ruler_height + ROW_GAP
+    }
+
+    pub fn compose(&self, diag: &Diagram) -> svg::Document {
+        self.used_chars.borrow_mut().clear();
+        let style = self.resolve_style(diag);
+        let theme = diag.theme();
+        // calc document width an height
+        let windows = self.windows(diag);
+        let total_wave_width = self.length(diag) * style.period_width;
+        let max_wave_width = windows.iter().map(|w| w.width(&style)).fold(0.0, f64::max);
+        let groups_width = self.groups_width(diag, &style);
+        let header_height = self.header_height(diag, &style);
+        let (_, lanes_height) = self.lane_tops(diag, &style);
+        let row_distance = self.row_distance(diag, &style);
+        let rows_height = windows.len() as f64 * row_distance - ROW_GAP;
+        let legend_entries = diag.legend_entries();
+        let legend_position = diag.legend().map(|l| l.position).unwrap_or_default();
+        let (legend_width, legend_height) = self.legend_size(&legend_entries, legend_position, &style);
+        let legend_right = !legend_entries.is_empty() && legend_position == LegendPosition::Right;
+        let legend_bottom = !legend_entries.is_empty() && legend_position == LegendPosition::Bottom;
+
+        let mut bottom = style.padding + header_height + rows_height;
+        if legend_right {
+            bottom = bottom.max(style.padding + header_height + style.wave_padding + legend_height);
+        }
+        // blocks below the diagram are stacked after the x-axis.
+        let mut below = bottom;
+        let mut next_top = bottom + if diag.xaxis().is_some() { xaxis_height(&style) } else { 0.0 } + FOOTER_GAP;
+        let legend_top = next_top;
+        if legend_bottom {
+            below = legend_top + legend_height;
+            next_top = below + FOOTER_GAP;
+        }
+        let footer_lines = self.footer_lines(diag);
+        let footer_top = next_top;
+        if !footer_lines.is_empty() {
+            below = footer_top + footer_lines.iter().map(|l| font::font_size(l.size) * style.font_scale * LINE_SPACING).sum::<f64>();
+        }
+        let height = below + style.padding;
+
+        // the diagram gets wider for long titles, captions and legends.
+        let text_width = footer_lines.iter().chain(self.header_lines(diag).iter())
+            .map(|l| self.text_width(&l.text, font::font_size(l.size) * style.font_scale))
+            .fold(0.0, f64::max);
+        let mut content_width = (max_wave_width + groups_width + style.name_width).max(text_width);
+        if legend_right {
+            content_width = content_width.max(max_wave_width + groups_width + style.name_width + LEGEND_GAP + legend_width);
+        }
+        if legend_bottom {
+            content_width = content_width.max(groups_width + style.name_width + legend_width);
+        }
+        let width = content_width + style.padding * 2.0;
+        let legend = if legend_right {
+            self.compose_legend(&legend_entries, legend_position, &style, &theme)
+                .translate(style.padding + groups_width + style.name_width + max_wave_width + LEGEND_GAP, style.padding + header_height + style.wave_padding)
+        } else {
+            self.compose_legend(&legend_entries, legend_position, &style, &theme)
+                .translate(style.padding + groups_width + style.name_width, legend_top)
+        };
+
+        let title = self.compose_header(diag, width, &style, &theme);
+
+        let mut rows = Group::new().set("id", "rows");
+        for (row, window) in windows.iter().enumerate() {
+            let top = style.padding + header_height + row as f64 * row_distance;
+            let group = Group::new()
+                .add(self.compose_groups(diag, *window, &style, &theme).translate(style.padding, top))
+                .add(self.compose_lanes(diag, *window, total_wave_width, &style, &theme).translate(style.padding + groups_width, top))
+                .add(self.compose_time_axis(diag, *window, &style, &theme)
+                    .translate(style.padding + groups_width + style.name_width, top + lanes_height));
+            rows.append(match window.row {
+                Some(row) => group.set("id", format!("row-{}", row)),
+                None => group,
+            });
+        }
+        let last_wave_width = windows.last().map(|w| w.width(&style)).unwrap_or_default();
 
         let xaxis = if let Some(xaxis) = diag.xaxis() {
-            println!("compose xaxis ");
             Group::new()
                 .add(Line::new()
-                    .set("x1", 0).set("y1", 10)
-                    .set("x2", max_wave_width).set("y2", 10)
-                    .with_color(Color::Lightgray)
+                    .set("x1", 0).set("y1", XAXIS_Y)
+                    .set("x2", last_wave_width).set("y2", XAXIS_Y)
+                    .with_color(theme.guide)
                     .rounded()
-                    .with_size(1.0)
+                    .with_size(style.axis_stroke_width)
                     .set("marker-end", "url(#arrowhead)")
                 )
-                .add(Text::from(&Label::from(xaxis.as_ref()).small().align(TextAnchor::End)).translate(max_wave_width, 20.0)) // 10.0 + ~ TEXT HEIGHT
+                .add(self.compose_text(&Label::from(xaxis.as_ref()).small().align(TextAnchor::End), last_wave_width, xaxis_text_y(&style), &style, &theme))
 
         } else { Group::new() };
+        let footer = self.compose_footer(&footer_lines, width, &style, &theme).translate(0.0, footer_top);
 
+        // the font is embedded after all texts are composed.
         svg::Document::new()
             .set("viewBox", (0,0,width,height))
-            .add(add_defs())
-            .add(bg)
+            .add(self.compose_font_defs(self.compose_window_defs(self.compose_animation_defs(add_defs(&theme), total_wave_width, lanes_height, &style), &windows, lanes_height, &style)))
+            .add(background(diag.background()))
             .add(title)
-            .add(lanes.translate(PADDING, PADDING + HEADER_HEIGHT))
-            .add(xaxis.translate(PADDING + DEFAULT_WAVE_OFFSET, height - PADDING))
-            
+            .add(rows)
+            .add(xaxis.translate(style.padding + groups_width + style.name_width, bottom))
+            .add(legend)
+            .add(footer)
+    }
+
+    // Compose the lanes of a window together with the annotations spanning several lanes.
+    fn compose_lanes(&self, diag: &Diagram, window: Window, total_wave_width: f64, style: &Style, theme: &Theme) -> Group {
+        let (lane_tops, lanes_height) = self.lane_tops(diag, style);
+        let mut lanes = Group::new().set("id", window.id("lanes"))
+            .add(self.compose_regions(diag.regions(), window, lanes_height, style, theme).set("id", window.id("regions")).translate(style.name_width, 0.0))
+            .add(self.compose_grid(Grid::Diagram, window, lanes_height, style, theme).translate(style.name_width, 0.0));
+
+        for (num,lane) in diag.lanes().iter().enumerate() {
+            lanes.append(self.compose_lane(num, lane, diag, window, style, theme).translate(0.0, lane_tops[num]));
+        }
+
+        lanes.append(self.compose_edges(diag, window, style, theme));
+
+        if let Some(cursor) = self.compose_animation_cursor(window, total_wave_width, lanes_height, style, theme) {
+            lanes.append(self.clip(window, cursor));
+        }
+        lanes
+    }
+
+    // Add the clip path which cuts off everything outside of the windows.
+    // It is used in the coordinates of the lanes, where the waves start right of the name column.
+    fn compose_window_defs(&self, defs: Definitions, windows: &[Window], lanes_height: f64, style: &Style) -> Definitions {
+        if !windows.iter().any(|w| w.clip) {
+            return defs;
+        }
+        let width = windows.iter().map(|w| w.width(style)).fold(0.0, f64::max);
+        defs.add(ClipPath::new()
+            .set("id", "window")
+            .set("clipPathUnits", "userSpaceOnUse")
+            .add(Rectangle::new()
+                .set("x", style.name_width).set("y", -style.lane_height())
+                .set("width", width).set("height", lanes_height + 2.0 * style.lane_height())))
     }
 
-    fn compose_lane(&self, num: usize, lane: &Lane, max_ww: f64) -> Group {
-        println!("Compose lane-{}",num);
+    // Cut off the parts of the element outside of the window.
+    fn clip<T: Node>(&self, window: Window, node: T) -> Group {
+        let group = Group::new().add(node);
+        if window.clip {
+            group.set("clip-path", "url(#window)")
+        } else {
+            group
+        }
+    }
+
+    // Width and height of the legend.
+    fn legend_size(&self, entries: &[LegendEntry], position: LegendPosition, style: &Style) -> (f64, f64) {
+        if entries.is_empty() {
+            return (0.0, 0.0);
+        }
+        let size = font::font_size(TextSize::Small) * style.font_scale;
+        let widths = entries.iter().map(|e| SWATCH_WIDTH + SWATCH_GAP + self.text_width(&e.description, size));
+        match position {
+            LegendPosition::Right => (widths.fold(0.0, f64::max), entries.len() as f64 * size * LINE_SPACING),
+            LegendPosition::Bottom => (widths.sum::<f64>() + (entries.len() - 1) as f64 * LEGEND_GAP, size * LINE_SPACING),
+        }
+    }
+
+    // Compose the legend's entries in a column or a row, each with a swatch left of its description.
+    fn compose_legend(&self, entries: &[LegendEntry], position: LegendPosition, style: &Style, theme: &Theme) -> Group {
+        let size = font::font_size(TextSize::Small) * style.font_scale;
+        let row_height = size * LINE_SPACING;
+        let mut group = Group::new().set("id", "legend");
+        let (mut x, mut y) = (0.0, 0.0);
+        for entry in entries {
+            let color = theme.signal_color(entry.color);
+            let middle = y + row_height / 2.0;
+            let line = Line::new()
+                .set("x1", x).set("y1", middle)
+                .set("x2", x + SWATCH_WIDTH).set("y2", middle)
+                .with_color(color);
+            match entry.swatch {
+                Swatch::Line => group.append(line.with_size(style.wave_stroke_width).rounded()),
+                Swatch::Dashed => group.append(line.with_size(1.0).dash(&style.marker_dash)),
+                Swatch::Area => group.append(Rectangle::new()
+                    .set("x", x).set("y", middle - size / 2.0)
+                    .set("width", SWATCH_WIDTH).set("height", size)
+                    .set("fill", color.to_string())
+                    .set("fill-opacity", Region::default().opacity)),
+            }
+            let label = Label::from(entry.description.as_str()).small().align(TextAnchor::Start);
+            group.append(self.compose_text(&label, x + SWATCH_WIDTH + SWATCH_GAP, middle + size * 0.35, style, theme));
+
+            match position {
+                LegendPosition::Right => y += row_height,
+                LegendPosition::Bottom => x += SWATCH_WIDTH + SWATCH_GAP + self.text_width(&entry.description, size) + LEGEND_GAP,
+            }
+        }
+        group
+    }
+
+    // Space reserved for the title and the subtitle, diagrams without both don't need a header.
+    fn header_height(&self, diag: &Diagram, style: &Style) -> f64 {
+        match diag.subtitle() {
+            Some(_) => style.header_height + font::font_size(TextSize::Smaller) * style.font_scale * LINE_SPACING,
+            None if diag.title().is_empty() => 0.0,
+            None => style.header_height,
+        }
+    }
+
+    fn header_lines(&self, diag: &Diagram) -> Vec<Label> {
+        let mut lines = vec![Label::from(diag.title())];
+        if let Some(subtitle) = diag.subtitle() {
+            lines.push(Label::from(subtitle.as_str()).with_size(TextSize::Smaller).color_with(Color::Lightgray));
+        }
+        lines
+    }
+
+    // The lines of the caption followed by the source line.
+    fn footer_lines(&self, diag: &Diagram) -> Vec<Label> {
+        let mut lines: Vec<Label> = diag.caption().map(|caption| caption.lines().into_iter()
+            .map(|line| Label::from(line).with_size(TextSize::Smaller).align(caption.align))
+            .collect()).unwrap_or_default();
+        if let Some(source) = diag.source() {
+            lines.push(Label::from(source.as_str()).small().align(TextAnchor::End).color_with(Color::Lightgray));
+        }
+        lines
+    }
+
+    // Compose the centered title and subtitle.
+    fn compose_header(&self, diag: &Diagram, width: f64, style: &Style, theme: &Theme) -> Group {
+        let mut group = Group::new().set("id", "title");
+        let mut y = style.padding;
+        for (num, line) in self.header_lines(diag).iter().enumerate() {
+            if num > 0 {
+                y += font::font_size(line.size) * style.font_scale * LINE_SPACING;
+            }
+            group.append(self.compose_text(line, width / 2.0, y, style, theme));
+        }
+        group
+    }
+
+    // Compose the lines below the diagram, which are placed by their alignment.
+    fn compose_footer(&self, lines: &[Label], width: f64, style: &Style, theme: &Theme) -> Group {
+        let mut group = Group::new().set("id", "footer");
+        let mut y = 0.0;
+        for line in lines {
+            let size = font::font_size(line.size) * style.font_scale;
+            let x = match line.anchor {
+                TextAnchor::Start => style.padding,
+                TextAnchor::Middle => width / 2.0,
+                TextAnchor::End => width - style.padding,
+            };
+            group.append(self.compose_text(line, x, y + size, style, theme));
+            y += size * LINE_SPACING;
+        }
+        group
+    }
+
+    fn compose_lane(&self, num: usize, lane: &Lane, diag: &Diagram, window: Window, style: &Style, theme: &Theme) -> Group {
         // tile and y-axis label goes to the left
-        // wave starts at wave_offset and goes till the end.
+        // wave starts at wave_offset and goes till the end of the window.
         let mut group = Group::new()
-            .set("id", format!("lane-{}",num));
+            .set("id", window.id(format!("lane-{}", num)));
 
-        let wave_offset = DEFAULT_WAVE_OFFSET;
-        let wave_end = wave_offset + max_ww; // max_ww gets calculated at the top of consume().
+        let wave_offset = style.name_width;
+        let wave_end = wave_offset + window.width(style);
+        // x coordinate of the time 0, which lies left of the waves for later windows.
+        let time_offset = wave_offset + window.origin(style);
 
         // compose y-axis labels (translate in y direction at 3.0 to match lane lines )
-        let y_axis_label_heigh = Text::from(&Label::from(lane.signal.y_axis.0.to_string()).small().align(TextAnchor::End).color_with(Color::Lightgray))
-            .translate(wave_offset-5.0, WAVE_PADDING_TOP + 3.0);
-        let y_axis_label_low = Text::from(&Label::from(lane.signal.y_axis.1.to_string()).small().align(TextAnchor::End).color_with(Color::Lightgray))
-            .translate(wave_offset-5.0, WAVE_PADDING_TOP + WAVE_HEIGHT + 3.0);
+        let y_axis_label_heigh = self.compose_text(&Label::from(lane.signal.y_axis.0.to_string()).small().align(TextAnchor::End).color_with(Color::Lightgray),
+            wave_offset-Y_AXIS_GAP, style.wave_padding + 3.0, style, theme);
+        let y_axis_label_low = self.compose_text(&Label::from(lane.signal.y_axis.1.to_string()).small().align(TextAnchor::End).color_with(Color::Lightgray),
+            wave_offset-Y_AXIS_GAP, style.wave_padding + style.wave_height + 3.0, style, theme);
 
         // todo!() add posibility to crate a label from the title.
-        let signal_name_label = Text::from(&signal_title_to_label(lane.signal.name.to_string(), lane.signal.color)
-            ).translate(wave_offset-15.0, WAVE_PADDING_TOP + WAVE_HEIGHT/2.0);
+        let signal_name_label = self.compose_text(&signal_title_to_label(lane.signal.name.to_string(), lane.signal.color),
+            wave_offset-NAME_GAP, style.wave_padding + style.wave_height/2.0, style, theme);
 
         group.append(y_axis_label_heigh);
         group.append(y_axis_label_low);
         group.append(signal_name_label);
 
-        println!("... y labels composed.");
+        group.append(self.compose_regions(&lane.regions, window, style.lane_height(), style, theme)
+            .set("id", window.id(format!("lane-{}-regions", num)))
+            .translate(wave_offset, 0.0));
 
         // compose dashed lane level lines
-        group.append(self.compose_lane_level_lines(wave_offset, wave_end).set("id", format!("lane-{}-level-lines",num)));
+        group.append(self.compose_lane_level_lines(wave_offset, wave_end, style, theme).set("id", window.id(format!("lane-{}-level-lines", num))));
+        group.append(self.compose_grid(Grid::Lanes, window, style.wave_height, style, theme).translate(wave_offset, style.wave_padding));
 
-        println!("... lane lines composed.");
+        group.append(self.clip(window, self.reveal(wave_path(&lane.signal, style)
+            .with_color(theme.signal_color(lane.signal.color))
+            .set("id", window.id(format!("lane-{}-wave", num)))
+            .translate(time_offset, style.wave_padding)
+        )));
 
-        group.append(Path::from(&lane.signal)
-            .set("id", format!("lane-{}-wave",num))
-            .translate(wave_offset, WAVE_PADDING_TOP)
-        );
-        println!("... signal composed.");
+        group.append(self.compose_cut_marks(window, wave_offset, wave_end, diag.background(), style, theme));
 
-        group.append(self.compose_lane_markers(&lane.markers)
-            .set("id",format!("lane-{}-markers",num))
-            .translate(wave_offset, 0.0));
-        println!("... markers composed.");
+        group.append(self.reveal(self.compose_lane_markers(&lane.markers, window, style, theme)
+            .set("id", window.id(format!("lane-{}-markers", num)))
+            .translate(time_offset, 0.0)));
         
+        group.append(self.reveal(self.compose_lane_dimensions(&lane.dimensions, diag, window, style, theme)
+            .set("id", window.id(format!("lane-{}-dimensions", num)))
+            .translate(time_offset, 0.0)));
+
         // compose labels at the bottom
-        group.append(self.compose_lane_labels(&lane.labels)
-            .set("id",format!("lane-{}-labels",num))
-            .translate(wave_offset, LANE_HEIGHT));
-        println!("... labels composed.");
+        group.append(self.reveal(self.compose_lane_labels(&lane.labels, window, style, theme)
+            .set("id", window.id(format!("lane-{}-labels", num)))
+            .translate(time_offset, style.lane_height())));
+
+        group
+    }
+
+    // Compose "…" in the middle of the wave at the edges where the diagram is cut off.
+    fn compose_cut_marks(&self, window: Window, start: f64, end: f64, background: Color, style: &Style, theme: &Theme) -> Group {
+        let mut group = Group::new();
+        let label = Label::from("…").color_with(Color::Lightgray);
+        let y = style.wave_padding + style.wave_height / 2.0;
+        let offset = font::font_size(TextSize::Normal) * style.font_scale / 2.0;
+        if window.cut_start {
+            group.append(self.compose_boxed_text(&label, start + offset, y, background, style, theme));
+        }
+        if window.cut_end {
+            group.append(self.compose_boxed_text(&label, end - offset, y, background, style, theme));
+        }
+        group.set("class", "cut-marks")
+    }
+
+    // Compose the shaded regions from the top to the given height with their labels at the top.
+    // Regions are cut to the window, which starts at x = 0.
+    fn compose_regions(&self, regions: &[Region], window: Window, height: f64, style: &Style, theme: &Theme) -> Group {
+        let size = font::font_size(TextSize::Small) * style.font_scale;
+        let mut group = Group::new();
+        for region in regions {
+            let (start, end) = match window.overlap(region.start.min(region.end), region.start.max(region.end)) {
+                Some(range) => range,
+                None => continue,
+            };
+            let x1 = (start - window.start) * style.period_width;
+            let x2 = (end - window.start) * style.period_width;
+            group.append(Rectangle::new()
+                .set("x", x1).set("y", 0)
+                .set("width", x2 - x1).set("height", height)
+                .set("fill", region.color.to_string())
+                .set("fill-opacity", region.opacity)
+                .set("stroke", "none"));
+            if !region.label.is_empty() {
+                group.append(self.compose_text(&Label::from(region.label.as_str()).small(), (x1 + x2) / 2.0, size, style, theme));
+            }
+        }
+        group
+    }
+
+    // Compose vertical lines at every (half) period of the window from 0 to the given height, if the style uses the grid mode.
+    fn compose_grid(&self, mode: Grid, window: Window, height: f64, style: &Style, theme: &Theme) -> Group {
+        let mut group = Group::new();
+        if style.grid != mode {
+            return group;
+        }
+        let step = if style.grid_half_periods { 0.5 } else { 1.0 };
+        let first = (window.start / step - 1e-9).ceil() as i64;
+        let last = (window.end / step + 1e-9).floor() as i64;
+        for num in first..=last {
+            let time = num as f64 * step;
+            let x = (time - window.start) * style.period_width;
+            group.append(Line::new()
+                .set("x1", x).set("y1", 0)
+                .set("x2", x).set("y2", height)
+                .with_color(theme.guide)
+                .with_size(style.grid_stroke_width)
+                .set("stroke-opacity", if time.fract() == 0.0 { 0.6 } else { 0.3 }));
+        }
+        group.set("class", "grid")
+    }
+
+    // Compose the edges between the named points, which sit in the middle of the waves.
+    // Edges with unknown points are skipped.
+    // Edges crossing the edge of the window are cut there and continue in the next window,
+    // their label is only shown in the window holding the middle of the edge.
+    fn compose_edges(&self, diag: &Diagram, window: Window, style: &Style, theme: &Theme) -> Group {
+        let mut group = Group::new().set("id", window.id("edges"));
+        let (lane_tops, _) = self.lane_tops(diag, style);
+        // x coordinate of the time 0, the edges are drawn in time coordinates.
+        let time_offset = style.name_width + window.origin(style);
+        let point = |name: &str| diag.find_point(name).map(|(lane, position)| (
+            position,
+            lane_tops[lane] + style.wave_padding + style.wave_height / 2.0,
+        ));
+        for (idx, edge) in diag.edges().iter().enumerate() {
+            let ((t1, y1), (t2, y2)) = match (point(&edge.from), point(&edge.to)) {
+                (Some(from), Some(to)) => (from, to),
+                _ => continue,
+            };
+            if window.overlap(t1.min(t2), t1.max(t2)).is_none() {
+                continue;
+            }
+            let (x1, x2) = (t1 * style.period_width, t2 * style.period_width);
+            let data = Data::new().move_to((x1, y1));
+            let data = match edge.line {
+                EdgeLine::Straight => data.line_to((x2, y2)),
+                EdgeLine::Spline => data.cubic_curve_to((x1 * 0.7 + x2 * 0.3, y1, x1 * 0.3 + x2 * 0.7, y2, x2, y2)),
+            };
+            let mut path = Path::new()
+                .set("fill", "none")
+                .set("stroke-width", 1)
+                .with_color(theme.text)
+                .set("d", data);
+            if edge.start_arrow {
+                path.assign("marker-start", "url(#edge-arrow)");
+            }
+            if edge.end_arrow {
+                path.assign("marker-end", "url(#edge-arrow)");
+            }
+            let mut edge_group = Group::new()
+                .set("id", window.id(format!("edge-{}", idx)))
+                .add(self.clip(window, self.reveal(Group::new().add(path).translate(time_offset, 0.0))));
+
+            if !edge.label.is_empty() && window.holds((t1 + t2) / 2.0) {
+                let label = Label::from(edge.label.as_str()).small();
+                let x = self.clamp_to_window(&label, (x1 + x2) / 2.0, window, style);
+                edge_group.append(self.reveal(self.compose_boxed_text(&label, x, (y1 + y2) / 2.0, diag.background(), style, theme)
+                    .translate(time_offset, 0.0)));
+            }
+            group.append(edge_group);
+        }
+        group
+    }
+
+    // Move the x coordinate of a centered boxed label, so the label stays inside a clipped window.
+    fn clamp_to_window(&self, label: &Label, x: f64, window: Window, style: &Style) -> f64 {
+        if !window.clip {
+            return x;
+        }
+        let half_width = (self.text_width(&label.text, font::font_size(label.size) * style.font_scale) + 4.0) / 2.0;
+        let (left, right) = (window.start * style.period_width + half_width, window.end * style.period_width - half_width);
+        x.min(right).max(left)
+    }
+
+    // Compose a text vertically centered at the given position on top of a box filled with the background,
+    // so it stays readable on top of lines.
+    fn compose_boxed_text(&self, label: &Label, x: f64, y: f64, background: Color, style: &Style, theme: &Theme) -> Group {
+        let size = font::font_size(label.size) * style.font_scale;
+        let width = self.text_width(&label.text, size) + 4.0;
+        let left = match label.anchor {
+            TextAnchor::Start => x - 2.0,
+            TextAnchor::Middle => x - width / 2.0,
+            TextAnchor::End => x - width + 2.0,
+        };
+        Group::new()
+            .add(Rectangle::new()
+                .set("x", left).set("y", y - size * 0.6)
+                .set("width", width).set("height", size * 1.2)
+                .set("fill", background.to_string()))
+            .add(self.compose_text(label, x, y + size * 0.35, style, theme))
+    }
+
+    // Place the dimension lines of a lane above (side 0) or below (side 1) the wave.
+    // Lines which would overlap another line (or its label) move one row further away from the wave.
+    // The placement doesn't depend on the window, so the lines keep their rows in all windows.
+    fn layout_dimensions(&self, dimensions: &[Dimension], diag: &Diagram, style: &Style) -> Vec<DimensionLayout> {
+        let size = font::font_size(TextSize::Small) * style.font_scale;
+        // occupied ranges (in px) of the rows above and below the wave.
+        let mut rows: [Vec<Vec<(f64, f64)>>; 2] = [Vec::new(), Vec::new()];
+
+        dimensions.iter().map(|dimension| {
+            let label = Label::from(dimension_label(dimension, diag.timebase())).small().color_with(dimension.color);
+            let x1 = dimension.start.min(dimension.end) * style.period_width;
+            let x2 = dimension.start.max(dimension.end) * style.period_width;
+            let label_width = self.text_width(&label.text, size) + 4.0;
+            // short lines get the arrows from outside and the label right of them.
+            let inside = x2 - x1 >= label_width + 2.0 * DIMENSION_ARROW_LENGTH;
+            let range = if inside {
+                (x1, x2)
+            } else {
+                (x1 - 2.0 * DIMENSION_ARROW_LENGTH, x2 + 2.0 * DIMENSION_ARROW_LENGTH + label_width)
+            };
+            let row_of = |side: &Vec<Vec<(f64, f64)>>| side.iter()
+                .position(|row| row.iter().all(|r| r.1 <= range.0 || range.1 <= r.0))
+                .unwrap_or(side.len());
+
+            let side = match dimension.side {
+                Side::Above => 0,
+                Side::Below => 1,
+                Side::Auto => if row_of(&rows[1]) < row_of(&rows[0]) { 1 } else { 0 },
+            };
+            let row = row_of(&rows[side]);
+            if row == rows[side].len() {
+                rows[side].push(Vec::new());
+            }
+            rows[side][row].push(range);
+            DimensionLayout { label, x1, x2, inside, side, row }
+        }).collect()
+    }
+
+    // Height of the stacked dimension lines above and below the lane's wave, 0 for a side without lines.
+    fn dimension_stacks(&self, lane: &Lane, diag: &Diagram, style: &Style) -> [f64; 2] {
+        let row_height = dimension_row_height(style);
+        let mut stacks = [0.0, 0.0];
+        for layout in self.layout_dimensions(&lane.dimensions, diag, style) {
+            let needed = 2.0 + (layout.row + 1) as f64 * row_height;
+            stacks[layout.side] = f64::max(stacks[layout.side], needed);
+        }
+        stacks
+    }
+
+    // The top of each lane inside the lanes of a row and the height of all lanes.
+    // Lanes with many stacked dimension lines take more space.
+    fn lane_tops(&self, diag: &Diagram, style: &Style) -> (Vec<f64>, f64) {
+        let mut tops = Vec::new();
+        let mut y = 0.0;
+        let mut labels_above = false;
+        for lane in diag.lanes() {
+            let [mut above, below] = self.dimension_stacks(lane, diag, style);
+            // the lane above draws its labels at its bottom edge, the lines stack below them.
+            if above > 0.0 && labels_above {
+                above += dimension_row_height(style);
+            }
+            let [above, below] = [above, below].map(|stack| f64::max(stack - style.wave_padding, 0.0));
+            tops.push(y + above);
+            y += above + style.lane_height() + below;
+            labels_above = !lane.labels.is_empty();
+        }
+        (tops, y)
+    }
+
+    // Compose the dimension lines of a lane above and below the wave.
+    // Lines crossing the edge of the window are cut there and lose their arrow on that side,
+    // their label is only shown in the window holding the middle of the line.
+    fn compose_lane_dimensions(&self, dimensions: &[Dimension], diag: &Diagram, window: Window, style: &Style, theme: &Theme) -> Group {
+        let row_height = dimension_row_height(style);
+
+        let mut group = Group::new();
+        for (dimension, layout) in dimensions.iter().zip(self.layout_dimensions(dimensions, diag, style)) {
+            let DimensionLayout { label, x1, x2, inside, side, row } = layout;
+            let (start, end) = match window.overlap(x1 / style.period_width, x2 / style.period_width) {
+                Some(range) => range,
+                None => continue,
+            };
+            let (cut_start, cut_end) = (start * style.period_width > x1 + 1e-9, end * style.period_width < x2 - 1e-9);
+            let show_label = window.holds((x1 + x2) / 2.0 / style.period_width);
+            let (x1, x2) = (start * style.period_width, end * style.period_width);
+
+            let offset = row_height / 2.0 + 2.0 + row as f64 * row_height;
+            let y = if side == 0 { style.wave_padding - offset } else { style.wave_padding + style.wave_height + offset };
+            let color = theme.text_color(dimension.color);
+
+            let arrow = |x: f64, dir: f64| Polygon::new()
+                .set("points", format!("{},{} {},{} {},{}",
+                    x, y,
+                    x - dir * DIMENSION_ARROW_LENGTH, y - DIMENSION_ARROW_WIDTH,
+                    x - dir * DIMENSION_ARROW_LENGTH, y + DIMENSION_ARROW_WIDTH))
+                .with_color(color);
+            let tick = |x: f64| Line::new()
+                .set("x1", x).set("y1", y - DIMENSION_ARROW_LENGTH)
+                .set("x2", x).set("y2", y + DIMENSION_ARROW_LENGTH)
+                .with_color(color)
+                .with_size(1.0);
+
+            let dir = if inside { 1.0 } else { -1.0 };
+            let text = if !show_label {
+                Group::new()
+            } else if cut_start || cut_end {
+                // keep the label at the middle of the whole line, as far as the window allows.
+                let middle = (dimension.start + dimension.end) / 2.0 * style.period_width;
+                self.compose_boxed_text(&label, self.clamp_to_window(&label, middle, window, style), y, diag.background(), style, theme)
+            } else if inside {
+                self.compose_boxed_text(&label, (x1 + x2) / 2.0, y, diag.background(), style, theme)
+            } else {
+                self.compose_boxed_text(&label.align(TextAnchor::Start), x2 + 2.0 * DIMENSION_ARROW_LENGTH + 2.0, y, diag.background(), style, theme)
+            };
+            let mut dimension_group = Group::new()
+                .set("class", "dimension")
+                .add(Line::new()
+                    .set("x1", if inside || cut_start { x1 } else { x1 - 2.0 * DIMENSION_ARROW_LENGTH }).set("y1", y)
+                    .set("x2", if inside || cut_end { x2 } else { x2 + 2.0 * DIMENSION_ARROW_LENGTH }).set("y2", y)
+                    .with_color(color)
+                    .with_size(1.0));
+            if !cut_start {
+                dimension_group.append(tick(x1));
+                dimension_group.append(arrow(x1, -dir));
+            }
+            if !cut_end {
+                dimension_group.append(tick(x2));
+                dimension_group.append(arrow(x2, dir));
+            }
+            group.append(dimension_group.add(text));
+        }
+        group
+    }
+
+    // Height of the time axis including its labels.
+    fn time_axis_height(&self, style: &Style) -> f64 {
+        MAJOR_TICK_LENGTH + font::font_size(TextSize::Small) * style.font_scale + 8.0
+    }
+
+    // Compose a ruler with ticks at every step and the time at the major ticks, which covers the window.
+    fn compose_time_axis(&self, diag: &Diagram, window: Window, style: &Style, theme: &Theme) -> Group {
+        let mut group = Group::new().set("id", window.id("time-axis"));
+        let time_axis = match diag.time_axis() {
+            Some(time_axis) if time_axis.step > 0.0 => time_axis,
+            _ => return group,
+        };
+        let tick = |x: f64, length: f64| Line::new()
+            .set("x1", x).set("y1", 0)
+            .set("x2", x).set("y2", length)
+            .with_color(theme.guide)
+            .with_size(style.axis_stroke_width);
+
+        group.append(Line::new()
+            .set("x1", 0).set("y1", 0)
+            .set("x2", window.width(style)).set("y2", 0)
+            .with_color(theme.guide)
+            .with_size(style.axis_stroke_width));
+
+        let periods = self.length(diag);
+        let steps = (periods / time_axis.step + 1e-9).floor() as usize;
+        let x_of = |time: f64| (time - window.start) * style.period_width;
+        for num in 0..=steps {
+            let time = num as f64 * time_axis.step;
+            if num < steps {
+                for minor in 1..=time_axis.minor {
+                    let time = time + minor as f64 * time_axis.step / (time_axis.minor + 1) as f64;
+                    if window.holds(time) {
+                        group.append(tick(x_of(time), MINOR_TICK_LENGTH));
+                    }
+                }
+            }
+            if window.holds(time) {
+                group.append(tick(x_of(time), MAJOR_TICK_LENGTH));
+            }
+        }
+
+        for (time, text) in time_axis_labels(diag, periods) {
+            if window.holds(time) {
+                let label = Label::from(text).small().color_with(Color::Lightgray);
+                let y = MAJOR_TICK_LENGTH + font::font_size(TextSize::Small) * style.font_scale;
+                group.append(self.compose_text(&label, x_of(time), y, style, theme));
+            }
+        }
+        group
+    }
 
+    // Compose a bracket with the name on the left of each lane group, nested groups are placed further right.
+    fn compose_groups(&self, diag: &Diagram, window: Window, style: &Style, theme: &Theme) -> Group {
+        let mut group = Group::new().set("id", window.id("groups"));
+        let (lane_tops, _) = self.lane_tops(diag, style);
+        for (idx, lane_group) in diag.groups().iter().enumerate() {
+            if lane_group.len == 0 {
+                continue;
+            }
+            let x = (diag.group_depth(idx) as f64 + 0.7) * style.group_width;
+            let top = lane_tops[lane_group.start] + style.wave_padding / 2.0;
+            let bottom = lane_tops[lane_group.end() - 1] + style.lane_height() - style.wave_padding / 2.0;
+            let bracket = Path::new()
+                .set("fill", "none")
+                .set("stroke-width", 1)
+                .with_color(theme.text)
+                .rounded()
+                .set("d", Data::new()
+                    .move_to((x + BRACKET_WIDTH, top))
+                    .horizontal_line_to(x)
+                    .vertical_line_to(bottom)
+                    .horizontal_line_to(x + BRACKET_WIDTH));
+            let name = self.compose_text(&Label::from(lane_group.name.as_str()).small(), 0.0, 0.0, style, theme)
+                .set("transform", format!("translate({},{}) rotate(-90)", x - 4.0, (top + bottom) / 2.0));
+            group.append(Group::new()
+                .set("id", window.id(format!("group-{}", idx)))
+                .add(bracket)
+                .add(name));
+        }
         group
     }
 
-    fn compose_lane_level_lines(&self, start: f64 , end: f64) -> Group {
+    // Add the clip path which reveals the waves to the definitions.
+    fn compose_animation_defs(&self, defs: Definitions, max_ww: f64, lanes_height: f64, style: &Style) -> Definitions {
+        let animation = match self.animation {
+            Some(animation) => animation,
+            None => return defs,
+        };
+        // the clip path is placed in the coordinates of the clipped elements, which start at time 0.
+        // renderers without animation support show the whole diagram.
+        // it reaches far enough above and below to cover the labels and dimension lines of all lanes.
+        let rect = Rectangle::new()
+            .set("x", -style.padding).set("y", -lanes_height)
+            .set("width", max_ww + style.padding).set("height", lanes_height * 3.0)
+            .add(animation.apply(Animate::new()
+                .set("attributeName", "width")
+                .set("from", style.padding)
+                .set("to", max_ww + style.padding)));
+        defs.add(ClipPath::new()
+            .set("id", "reveal")
+            .set("clipPathUnits", "userSpaceOnUse")
+            .add(rect))
+    }
+
+    // Compose a line which moves along with the revealed waves, it passes the window while the window gets revealed.
+    fn compose_animation_cursor(&self, window: Window, max_ww: f64, height: f64, style: &Style, theme: &Theme) -> Option<Line> {
+        let animation = self.animation.filter(|a| a.cursor)?;
+        Some(v_dashed_line(style.name_width + window.origin(style), 0.0, height, style, theme)
+            .with_color(Color::Red)
+            .dash("none")
+            .set("id", window.id("cursor"))
+            .add(animation.apply(AnimateTransform::new()
+                .set("attributeName", "transform")
+                .set("type", "translate")
+                .set("from", "0 0")
+                .set("to", format!("{} 0", max_ww)))))
+    }
+
+    // Embed the bundled font with the characters of all texts composed so far.
+    fn compose_font_defs(&self, defs: Definitions) -> Definitions {
+        if self.text_mode != TextMode::Embedded {
+            return defs;
+        }
+        defs.add(StyleSheet::new(font::font_face(&self.used_chars.take())))
+    }
+
+    // Compose a text at the given position according to the text mode.
+    fn compose_text(&self, label: &Label, x: f64, y: f64, style: &Style, theme: &Theme) -> Group {
+        let size = font::font_size(label.size) * style.font_scale;
+        let color = theme.text_color(label.color);
+        let group = Group::new();
+        let group = match self.text_mode {
+            TextMode::Font => group.add(Text::from(label)
+                .with_color(color)
+                .set("font-family", style.font_family.as_str())
+                .with_size(size)),
+            TextMode::Embedded => {
+                self.used_chars.borrow_mut().extend(label.text.chars());
+                group.add(Text::from(label)
+                    .with_color(color)
+                    .set("font-family", font::FONT_FAMILY)
+                    .with_size(size))
+            }
+            TextMode::Paths => group.add(Path::new()
+                .set("fill", color.to_string())
+                .set("d", font::text_to_path(&label.text, size, label.anchor))),
+        };
+        group.translate(x, y)
+    }
+
+    // Clip the element to the revealed part if the diagram is animated.
+    fn reveal<T: Node>(&self, mut node: T) -> T {
+        if self.animation.is_some() {
+            node.assign("clip-path", "url(#reveal)");
+        }
+        node
+    }
+
+    fn compose_lane_level_lines(&self, start: f64 , end: f64, style: &Style, theme: &Theme) -> Group {
         Group::new()
-            .add(h_dashed_line(start, end, 0.0))
-            .add(h_dashed_line(start, end, WAVE_HEIGHT/2.0))
-            .add(h_dashed_line(start, end, WAVE_HEIGHT))
-            .translate(0.0, WAVE_PADDING_TOP)
+            .add(h_dashed_line(start, end, 0.0, style, theme))
+            .add(h_dashed_line(start, end, style.wave_height/2.0, style, theme))
+            .add(h_dashed_line(start, end, style.wave_height, style, theme))
+            .translate(0.0, style.wave_padding)
     }
 
-    fn compose_lane_markers(&self, markers: &Vec<LineMarker>) -> Group {
-        let top_y = WAVE_PADDING_TOP/2.0;
-        let bottom_y = WAVE_PADDING_TOP + WAVE_HEIGHT + WAVE_PADDING_BOTTOM/2.0;
+    fn compose_lane_markers(&self, markers: &[LineMarker], window: Window, style: &Style, theme: &Theme) -> Group {
+        let top_y = style.wave_padding/2.0;
+        let bottom_y = style.wave_padding + style.wave_height + style.wave_padding/2.0;
 
         let mut group = Group::new();
-        for marker in markers {
-            group.append(v_dashed_line(marker.position()*WAVE_PERIOD_WIDTH, top_y, bottom_y).with_color(marker.color).with_size(marker.thickness));
+        for marker in markers.iter().filter(|m| window.holds(m.position())) {
+            let line = v_dashed_line(marker.position()*style.period_width, top_y, bottom_y, style, theme).with_color(theme.guide_color(marker.color)).with_size(marker.thickness);
+            group.append(if marker.dashed { line } else { line.dash("none") });
         }
         group
     }
 
-    fn compose_lane_labels(&self, labels: &Vec<Label>) -> Group 
+    fn compose_lane_labels(&self, labels: &[Label], window: Window, style: &Style, theme: &Theme) -> Group 
     {
         let mut group = Group::new();
-        for label in labels {
-            group.append(Text::from(label).translate(label.position() * WAVE_PERIOD_WIDTH, 0.0));
+        for label in labels.iter().filter(|l| window.holds(l.position())) {
+            group.append(self.compose_text(label, label.position() * style.period_width, 0.0, style, theme));
         }
         group
     }
 }
 
+impl Backend for Compositor {
+    type Output = svg::Document;
+
+    fn render(&self, diag: &Diagram) -> Self::Output {
+        self.compose(diag)
+    }
+}
+
+impl Animation {
+    // Set the timing attributes of an animation element.
+    fn apply<T: Node>(&self, mut node: T) -> T {
+        node.assign("dur", format!("{}s", self.duration));
+        if self.repeat {
+            node.assign("repeatCount", "indefinite");
+        } else {
+            node.assign("fill", "freeze");
+        }
+        node
+    }
+}
+
 // helper functions
 fn get_max_wave_len(diagram: &Diagram) -> u32 {
     if let Some(max_sig) = diagram.lanes().iter().map(|l| &l.signal ).max() {
-        return max_sig.len();
+        max_sig.len()
     } else {
-        return 0;
+        0
+    }
+}
+
+// The labels of the time axis' major ticks up to the given time as (time, text) pairs.
+fn time_axis_labels(diag: &Diagram, periods: f64) -> Vec<(f64, String)> {
+    let time_axis = match diag.time_axis() {
+        Some(time_axis) if time_axis.step > 0.0 && time_axis.labels => time_axis,
+        _ => return Vec::new(),
+    };
+    // all labels use the unit of the step, e.g. 0 ns, 20 ns, 40 ns.
+    let unit = diag.timebase().map(|timebase| axis::time_unit(time_axis.step * timebase));
+    let steps = (periods / time_axis.step + 1e-9).floor() as usize;
+    (0..=steps).map(|num| {
+        let time = num as f64 * time_axis.step;
+        let text = match (diag.timebase(), unit) {
+            (Some(timebase), Some(unit)) => axis::format_time(time * timebase, unit),
+            _ => axis::format_number(time),
+        };
+        (time, text)
+    }).collect()
+}
+
+// Baseline of the x-axis text, which is placed below the arrow head.
+fn xaxis_text_y(style: &Style) -> f64 {
+    XAXIS_Y + 3.5 * style.axis_stroke_width + font::font_size(TextSize::Small) * style.font_scale
+}
+
+// Space taken by the x-axis arrow and its text below the lanes.
+fn xaxis_height(style: &Style) -> f64 {
+    xaxis_text_y(style) + 0.3 * font::font_size(TextSize::Small) * style.font_scale
+}
+
+// Distance between the rows of stacked dimension lines.
+fn dimension_row_height(style: &Style) -> f64 {
+    font::font_size(TextSize::Small) * style.font_scale + 2.0
+}
+
+// The dimension's label or its measured duration, in physical units if the diagram has a timebase.
+fn dimension_label(dimension: &Dimension, timebase: Option<f64>) -> String {
+    if !dimension.label.is_empty() {
+        return dimension.label.clone();
+    }
+    match timebase {
+        Some(timebase) => {
+            let seconds = dimension.duration() * timebase;
+            axis::format_time(seconds, axis::time_unit(seconds))
+        }
+        None => axis::format_number(dimension.duration()),
     }
 }
 
 // Add defs
-fn add_defs() -> Definitions {
-    let defs = Definitions::new()
+fn add_defs(theme: &Theme) -> Definitions {
+    Definitions::new()
         .add(Marker::new()
             .set("id", "arrowhead")
             .set("markerWidth", 10)
@@ -203,28 +1209,45 @@ fn add_defs() -> Definitions {
             .set("refX", 0)
             .set("refY", 3.5)
             .set("orient", "auto")
-            .add(Polygon::new().set("points", "0 0, 10 3.5, 0 7").with_color(Color::Lightgray))
-        );
-    defs
+            .add(Polygon::new().set("points", "0 0, 10 3.5, 0 7").with_color(theme.guide))
+        )
+        .add(Marker::new()
+            .set("id", "edge-arrow")
+            .set("markerWidth", 8)
+            .set("markerHeight", 6)
+            .set("refX", 8)
+            .set("refY", 3)
+            .set("orient", "auto-start-reverse")
+            .add(Polygon::new().set("points", "0 0, 8 3, 0 6").with_color(theme.text))
+        )
 }
 
-fn h_dashed_line(x1: f64,x2: f64,y: f64) -> Line {
+fn background(color: Color) -> Rectangle {
+    Rectangle::new()
+        .set("id", "background")
+        .set("fill", color.to_string())
+        .set("stroke", "none")
+        .set("width", "100%")
+        .set("height", "100%")
+}
+
+fn h_dashed_line(x1: f64,x2: f64,y: f64, style: &Style, theme: &Theme) -> Line {
     Line::new()
         .set("x1", x1).set("y1", y)
         .set("x2", x2).set("y2", y)
-        .with_color(Color::Lightgray)
+        .with_color(theme.guide)
         .rounded()
-        .dash("10 6")
-        .with_size(0.5)
+        .dash(&style.guide_dash)
+        .with_size(style.guide_stroke_width)
 }
 
-fn v_dashed_line(x: f64, y1: f64,y2: f64) -> Line {
+fn v_dashed_line(x: f64, y1: f64,y2: f64, style: &Style, theme: &Theme) -> Line {
     Line::new()
         .set("x1", x).set("y1", y1)
         .set("x2", x).set("y2", y2)
-        .with_color(Color::Lightgray)
+        .with_color(theme.guide)
         .with_size(1.0)
-        .dash("3 3")
+        .dash(&style.marker_dash)
         .rounded()
 }
 
@@ -242,84 +1265,90 @@ impl From<&Label> for Text {
             .align_to(label.anchor)
             .set("font-family", "Segoe Print")
             .set("font-size", label.size.to_string())
-            .add(svg::node::Text::new(label.text.clone()))
+            .add(svg::node::Text::new(escape(&label.text)))
     }
 }
 
+// Escape the characters with a meaning in xml, svg doesn't do that for text nodes.
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
 impl From<&Signal> for Path {
     fn from(signal: &Signal) -> Self {
-        Path::new()
-        .set("fill", "none")
-        .set("stroke-width", 3)
-        .with_color(signal.color)
-        .rounded()
-        .set("d", Data::from(signal))
+        wave_path(signal, &Style::default())
     }
 }
 
 impl From<&Signal> for Data {
-    // compose svg paths data from a signal
-    fn from(sig: &Signal) -> Self {
-        // Set start conditions. 
-        let mut prev_value = match sig.wave.levels.first() {
-            Some(Level::High) => Level::High,
-            Some(Level::Down) => Level::High,
-            _ => Level::Low,
-        };
+    fn from(signal: &Signal) -> Self {
+        wave_data(signal, &Style::default())
+    }
+}
 
-        //log::debug!("First value is {:?}", prev_value);
-        
-        let y = match prev_value {
-            Level::Low => WAVE_HEIGHT,
-            Level::High => 0.0,
-            _ => WAVE_HEIGHT/2.0,
-        };
+fn wave_path(signal: &Signal, style: &Style) -> Path {
+    Path::new()
+    .set("fill", "none")
+    .set("stroke-width", style.wave_stroke_width)
+    .with_color(signal.color)
+    .rounded()
+    .set("d", wave_data(signal, style))
+}
 
-        //log::debug!("Set y to {}", y);
-
-        let mut data = Data::new()
-            .move_to((0,y))
-            .horizontal_line_by(sig.phase * WAVE_PERIOD_WIDTH);
-
-            for value in sig.wave.levels.iter() {
-                if &prev_value == value {
-                    data = data.horizontal_line_by(WAVE_PERIOD_WIDTH  * sig.period);
-                } else {
-                    match value {
-                        Level::Low => {
-                            data = data
-                            .vertical_line_by(WAVE_HEIGHT)
-                            .horizontal_line_by(WAVE_PERIOD_WIDTH  * sig.period);
-                            prev_value = Level::Low;
-                        }
-                        Level::High => {
-                            data = data
-                            .vertical_line_by(-(WAVE_HEIGHT))
-                            .horizontal_line_by(WAVE_PERIOD_WIDTH * sig.period);
-                            prev_value = Level::High;
-                        }
-                        Level::Up => {
-                            data = data
-                                .vertical_line_to(WAVE_HEIGHT)
-                                .horizontal_line_by((WAVE_PERIOD_WIDTH/2.0)  * sig.period)
-                                .vertical_line_by(-(WAVE_HEIGHT ))
-                                .horizontal_line_by((WAVE_PERIOD_WIDTH/2.0)  * sig.period);
-                            prev_value = Level::High;
-                        }
-                        Level::Down => {
-                            data = data
-                                .vertical_line_to(0)
-                                .horizontal_line_by((WAVE_PERIOD_WIDTH/2.0)  * sig.period)
-                                .vertical_line_by(WAVE_HEIGHT)
-                                .horizontal_line_by((WAVE_PERIOD_WIDTH/2.0)  * sig.period);
-                            prev_value = Level::Low;
-                        }
-                        _ => { unimplemented!()}
-                    }
-                }            
+// compose svg paths data from a signal
+fn wave_data(sig: &Signal, style: &Style) -> Data {
+    let (wave_height, period_width) = (style.wave_height, style.period_width);
+
+    let level_y = |level: Level| match level {
+        Level::High => 0.0,
+        Level::Idle => wave_height/2.0,
+        _ => wave_height,
+    };
+
+    // Set start conditions. 
+    let mut prev_value = match sig.wave.levels.first() {
+        Some(Level::High) => Level::High,
+        Some(Level::Down) => Level::High,
+        Some(Level::Idle) => Level::Idle,
+        _ => Level::Low,
+    };
+
+    let mut data = Data::new()
+        .move_to((0.0,level_y(prev_value)))
+        .horizontal_line_by(sig.phase * period_width);
+
+    for value in sig.wave.levels.iter() {
+        if &prev_value == value {
+            data = data.horizontal_line_by(period_width * sig.period);
+        } else {
+            match value {
+                Level::Up => {
+                    data = data
+                        .vertical_line_to(wave_height)
+                        .horizontal_line_by((period_width/2.0) * sig.period)
+                        .vertical_line_by(-wave_height)
+                        .horizontal_line_by((period_width/2.0) * sig.period);
+                    prev_value = Level::High;
+                }
+                Level::Down => {
+                    data = data
+                        .vertical_line_to(0)
+                        .horizontal_line_by((period_width/2.0) * sig.period)
+                        .vertical_line_by(wave_height)
+                        .horizontal_line_by((period_width/2.0) * sig.period);
+                    prev_value = Level::Low;
+                }
+                // Low, High and Idle (drawn at the middle level)
+                level => {
+                    data = data
+                        .vertical_line_to(level_y(*level))
+                        .horizontal_line_by(period_width * sig.period);
+                    prev_value = *level;
+                }
             }
-        data
+        }
     }
+    data
 }
 
 // Transformable
@@ -428,4 +1457,298 @@ impl Rounded for Path {
         self.set("stroke-linejoin","round")
         .set("stroke-linecap", "round")
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn idle_levels_are_drawn_at_the_middle() {
+        let diag = parse::from_json_str(r#"{"signals":[{"name":"A","wave":"lhiih"}]}"#).unwrap();
+        let style = Style::default();
+        let data = svg::node::Value::from(wave_data(&diag.lanes()[0].signal, &style)).to_string();
+        assert!(data.contains(&format!("V{}", style.wave_height / 2.0)));
+        Compositor::default().compose(&diag);
+    }
+
+    #[test]
+    fn embedded_font_covers_all_texts() {
+        use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+
+        let diag = parse::from_json_str(r#"{
+            "config":{"title":"Títle","subtitle":"Sub","caption":{"text":"Capt","number":3},"source":"Src","xaxis":"time",
+                "timebase":"2.5us","time_axis":{"step":2,"minor":1},"legend":true},
+            "signals":[{"group":"Grüppe","signals":[
+                {"name":"clk","wave":"lhlhlhlhlhlh","node":".a..","ticks":["T0","T1"],"markers":[{"at":[3],"description":"Mark"}],
+                    "regions":[{"start":1,"end":2,"label":"Reg"}]},
+                {"name":"dat","wave":"lhiidulhlhhl","node":"...b","yaxis":{"top":"Hi","bottom":"Qo"},"description":"Desc",
+                    "dimensions":[{"start":1,"end":4},{"start":5,"end":6,"label":"Dim"}]}]}],
+            "edges":["a~>b Edge"]}"#).unwrap();
+        let mut comp = Compositor::default();
+        comp.text_mode(TextMode::Embedded).wrap(5).zoom(1.0, 11.0).cut_marks(true);
+        let svg = comp.compose(&diag).to_string();
+        assert!(svg.contains("\nQo\n"));
+
+        let data = svg.split("base64,").nth(1).and_then(|data| data.split('"').next()).expect("The font isn't embedded");
+        let font = BASE64.decode(data).unwrap();
+        let face = ttf_parser::Face::parse(&font, 0).unwrap();
+        let mut texts = svg.split("<text").skip(1)
+            .map(|text| text[text.find('>').unwrap() + 1..text.find("</text>").unwrap()].to_string())
+            .peekable();
+        assert!(texts.peek().is_some());
+        for text in texts {
+            let text = text.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&");
+            assert!(text.trim().chars().all(|c| face.glyph_index(c).is_some()), "{:?} isn't covered by the embedded font", text);
+        }
+        // the font only keeps the used glyphs.
+        assert!(face.glyph_index('Z').is_none());
+    }
+
+    #[test]
+    fn wrapped_rows_have_unique_ids() {
+        let diag = parse::from_json_str(r#"{"config":{"time_axis":true},
+            "signals":[{"group":"G","signals":[{"name":"A","wave":"lhlhlhlhlhlhlhlh","node":".a......b"},{"name":"B","wave":"lhlhlhlhlhlhlhlh"}]}],
+            "edges":["a->b"],"regions":[{"start":1,"end":12}]}"#).unwrap();
+        let svg = Compositor::default().wrap(5).compose(&diag).to_string();
+        let ids: Vec<&str> = svg.split(" id=\"").skip(1).map(|s| &s[..s.find('"').unwrap()]).collect();
+        let unique: BTreeSet<&str> = ids.iter().copied().collect();
+        assert_eq!(ids.len(), unique.len());
+        assert!(unique.contains("row-3-lane-1-wave"));
+    }
+
+    #[test]
+    fn texts_are_escaped() {
+        let diag = parse::from_json_str(r#"{"config":{"title":"R&D <t>"},"signals":[{"name":"<img src=x onerror=alert(1)>","wave":"lh"}]}"#).unwrap();
+        let svg = Compositor::default().compose(&diag).to_string();
+        assert!(!svg.contains("<img") && !svg.contains("<t>"));
+        assert!(svg.contains("&lt;img src=x onerror=alert(1)&gt;"));
+        assert!(svg.contains("R&amp;D &lt;t&gt;"));
+    }
+
+    // Value of the numeric attribute in the start tag of an element.
+    fn attribute(element: &str, name: &str) -> f64 {
+        let value = &element[element.find(&format!(" {}=\"", name)).unwrap() + name.len() + 3..];
+        value[..value.find('"').unwrap()].parse().unwrap()
+    }
+
+    #[test]
+    fn labels_of_cut_dimensions_stay_in_the_window() {
+        let diag = parse::from_json_str(r#"{"config":{"timebase":"10ns"},
+            "signals":[{"name":"A","wave":"lhhllhhlll","dimensions":[{"start":1,"end":4},{"start":1.5,"end":3.5,"label":"t_SU"},{"start":6,"end":9}]}]}"#).unwrap();
+        let comp = Compositor::default();
+        let style = comp.resolve_style(&diag);
+        let window = Window { start: 2.5, end: 7.0, clip: true, cut_start: false, cut_end: false, closed: true, row: None };
+        let dimensions = comp.compose_lane_dimensions(&diag.lanes()[0].dimensions, &diag, window, &style, &Theme::default()).to_string();
+        let boxes: Vec<&str> = dimensions.split("<rect").skip(1).collect();
+        assert_eq!(boxes.len(), 2);
+        for rect in boxes {
+            let (x, width) = (attribute(rect, "x"), attribute(rect, "width"));
+            assert!(x >= window.start * style.period_width - 1e-9 && x + width <= window.end * style.period_width + 1e-9, "label at {}..{}", x, x + width);
+        }
+    }
+
+    #[test]
+    fn stacked_dimensions_leave_room_for_the_labels_above() {
+        let lane_tops = |ticks: &str| {
+            let diag = parse::from_json_str(&format!(r#"{{"signals":[{{"name":"A","wave":"lhlh","ticks":[{}]}},
+                {{"name":"B","wave":"lhlh","dimensions":[{{"start":1,"end":3,"side":"above"}},{{"start":1.5,"end":2.5,"side":"above"}}]}}]}}"#, ticks)).unwrap();
+            let comp = Compositor::default();
+            let style = comp.resolve_style(&diag).into_owned();
+            (comp.lane_tops(&diag, &style).0, style)
+        };
+        let (without_labels, style) = lane_tops("");
+        let (with_labels, _) = lane_tops(r#""t1","t2""#);
+        // two rows of lines reach beyond the wave padding.
+        let stack = 2.0 + 2.0 * dimension_row_height(&style);
+        assert!((without_labels[1] - (style.lane_height() + stack - style.wave_padding)).abs() < 1e-9);
+        assert!((with_labels[1] - without_labels[1] - dimension_row_height(&style)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn row_boundaries_are_drawn_once() {
+        let diag = parse::from_json_str(r#"{"config":{"timebase":"20ns","time_axis":{"step":1}},
+            "signals":[{"name":"A","wave":"lhlhlhlhlh","markers":[{"at":[5, 10]}]}]}"#).unwrap();
+        let svg = Compositor::default().wrap(5).compose(&diag).to_string();
+        assert_eq!(svg.matches("\n100 ns\n").count(), 1);
+        assert_eq!(svg.matches("\n200 ns\n").count(), 1);
+        let markers = |row: usize| {
+            let group = &svg[svg.find(&format!("id=\"row-{}-lane-0-markers\"", row)).unwrap()..];
+            group[..group.find("</g>").unwrap()].matches("<line").count()
+        };
+        assert_eq!((markers(0), markers(1)), (0, 2));
+    }
+
+    #[test]
+    fn xaxis_text_scales_with_the_font() {
+        for style in [Style::default(), Style::presentation()] {
+            let size = font::font_size(TextSize::Small) * style.font_scale;
+            // the text's top stays below the arrow head and its descent inside the reserved space.
+            assert!(xaxis_text_y(&style) - 0.8 * size >= XAXIS_Y + 3.5 * style.axis_stroke_width);
+            assert!(xaxis_height(&style) >= xaxis_text_y(&style) + 0.25 * size);
+        }
+    }
+
+    #[test]
+    fn edges_are_revealed_with_the_waves() {
+        let reveals = |edges: &str| {
+            let diag = parse::from_json_str(&format!(r#"{{"signals":[{{"name":"A","wave":"lhlh","node":".a.b"}}],"edges":[{}]}}"#, edges)).unwrap();
+            Compositor::default().animate(Animation::default()).compose(&diag).to_string().matches("url(#reveal)").count()
+        };
+        // the path and the label of the edge.
+        assert_eq!(reveals(r#""a~>b t_pd""#), reveals("") + 2);
+    }
+
+    // Width and height of the composed document.
+    fn view_box(svg: &str) -> (f64, f64) {
+        let value = &svg[svg.find("viewBox=\"").unwrap() + 9..];
+        let values: Vec<f64> = value[..value.find('"').unwrap()].split_whitespace().map(|v| v.parse().unwrap()).collect();
+        (values[2], values[3])
+    }
+
+    #[test]
+    fn styles_set_the_layout() {
+        let diag = parse::from_json_str(r#"{"signals":[{"name":"A","wave":"lhlh"}]}"#).unwrap();
+        for style in [Style::default(), Style::compact(), Style::print(), Style::presentation()] {
+            let mut comp = Compositor::default();
+            comp.style(style.clone());
+            let svg = comp.compose(&diag).to_string();
+            let name_width = comp.resolve_style(&diag).name_width;
+            assert_eq!(view_box(&svg), (2.0 * style.padding + name_width + 4.0 * style.period_width, 2.0 * style.padding + style.lane_height()));
+            assert!(svg.contains(&format!("stroke-width=\"{}\"", style.wave_stroke_width)));
+        }
+    }
+
+    #[test]
+    fn dark_theme_replaces_black_and_lightgray() {
+        let diag = parse::from_json_str(r#"{"config":{"title":"Dark","subtitle":"theme","xaxis":"t","theme":"dark","time_axis":true,"legend":true},
+            "signals":[{"name":"A","wave":"lhlh","node":".a.b","ticks":["x"],"markers":[{"at":[1]}],"dimensions":[{"start":1,"end":3}]},
+                {"name":"B","wave":"hlhl","color":"Red","description":"red"}],
+            "edges":["a->b"]}"#).unwrap();
+        let svg = Compositor::default().compose(&diag).to_string();
+        let theme = Theme::dark();
+        for color in [Color::Black, Color::Lightgray] {
+            assert!(!svg.contains(&color.to_string()), "{} is used", color);
+        }
+        for color in [theme.background, theme.text, theme.signal, theme.guide, Color::Red] {
+            assert!(svg.contains(&color.to_string()), "{} is missing", color);
+        }
+        let blue = parse::from_json_str(r#"{"config":{"theme":{"preset":"dark","background":"Blue"}},"signals":[{"name":"A","wave":"lh"}]}"#).unwrap();
+        assert_eq!(blue.background(), Color::Blue);
+        assert!(Compositor::default().compose(&blue).to_string().contains(&Color::Blue.to_string()));
+    }
+
+    #[test]
+    fn regions_are_shaded_behind_the_waves() {
+        let diag = parse::from_json_str(r#"{"signals":[{"name":"A","wave":"lhlh","regions":[{"start":1,"end":2,"label":"bit"}]},{"name":"B","wave":"hlhl"}],
+            "regions":[{"start":3,"end":1.5,"color":"Red","opacity":0.5}]}"#).unwrap();
+        let comp = Compositor::default();
+        let style = comp.resolve_style(&diag);
+        let svg = comp.compose(&diag).to_string();
+        let group = |id: &str| {
+            let start = svg.find(&format!("id=\"{}\"", id)).unwrap();
+            (start, &svg[start..start + svg[start..].find("</g>").unwrap()])
+        };
+        let (lane_start, lane) = group("lane-0-regions");
+        let (all_start, all) = group("regions");
+        assert!(lane_start < group("lane-0-wave").0 && all_start < lane_start);
+        assert_eq!([attribute(lane, "x"), attribute(lane, "width"), attribute(lane, "height"), attribute(lane, "fill-opacity")],
+            [style.period_width, style.period_width, style.lane_height(), Region::default().opacity]);
+        assert!(lane.contains("\nbit\n"));
+        assert_eq!([attribute(all, "x"), attribute(all, "width"), attribute(all, "height"), attribute(all, "fill-opacity")],
+            [1.5 * style.period_width, 1.5 * style.period_width, 2.0 * style.lane_height(), 0.5]);
+        assert!(all.contains(&Color::Red.to_string()));
+    }
+
+    #[test]
+    fn subtitle_and_footer_grow_the_layout() {
+        let compose = |config: &str| {
+            let diag = parse::from_json_str(&format!(r#"{{"config":{},"signals":[{{"name":"A","wave":"lhlh"}}]}}"#, config)).unwrap();
+            Compositor::default().compose(&diag).to_string()
+        };
+        let style = Style::default();
+        let line = |size| font::font_size(size) * style.font_scale * LINE_SPACING;
+        let (width, height) = view_box(&compose(r#"{"title":"T"}"#));
+        let (subtitle_width, subtitle_height) = view_box(&compose(r#"{"title":"T","subtitle":"sub"}"#));
+        assert!(subtitle_width == width && (subtitle_height - height - line(TextSize::Smaller)).abs() < 1e-9);
+
+        let svg = compose(r#"{"title":"T","caption":{"text":"a\nb","align":"left","number":1},"source":"src"}"#);
+        let (footer_width, footer_height) = view_box(&svg);
+        assert!(footer_width == width && (footer_height - height - FOOTER_GAP - 2.0 * line(TextSize::Smaller) - line(TextSize::Small)).abs() < 1e-9);
+        // each line is placed by its alignment.
+        let footer = &svg[svg.find("id=\"footer\"").unwrap()..];
+        let lines: Vec<(f64, &str)> = footer.split("<g transform=\"translate(").skip(1)
+            .map(|line| (line[..line.find(',').unwrap()].parse().unwrap(), line.split('\n').nth(2).unwrap()))
+            .collect();
+        assert_eq!(lines, [(style.padding, "Figure 1: a"), (style.padding, "b"), (width - style.padding, "src")]);
+    }
+
+    #[test]
+    fn legend_lists_the_described_colors() {
+        let diag = |legend: &str| parse::from_json_str(&format!(r#"{{"config":{{"legend":{}}},
+            "signals":[{{"name":"CLK","wave":"lhlh","description":"clock","markers":[{{"at":[1,3],"color":"Red","dashed":true,"description":"sample"}}]}},
+                {{"name":"D","wave":"hlhl"}}]}}"#, legend)).unwrap();
+        let phase = LegendEntry::new(Color::Blue, Swatch::Area, "phase");
+        let custom = r#"{"entries":[{"color":"Blue","swatch":"area","description":"phase"}]"#;
+        assert_eq!(diag(&format!("{}}}", custom)).legend_entries(),
+            [phase.clone(), LegendEntry::new(Color::Black, Swatch::Line, "clock"), LegendEntry::new(Color::Red, Swatch::Dashed, "sample")]);
+        assert_eq!(diag(&format!(r#"{},"auto":false}}"#, custom)).legend_entries(), [phase]);
+
+        let size = |legend: &str| view_box(&Compositor::default().compose(&diag(legend)).to_string());
+        let (width, height) = size("false");
+        let (right_width, right_height) = size("true");
+        let (bottom_width, bottom_height) = size(r#"{"position":"bottom"}"#);
+        assert!(right_width > width && right_height == height);
+        assert!(bottom_width == width && bottom_height > height);
+        let svg = Compositor::default().compose(&diag("true")).to_string();
+        let legend = &svg[svg.find("id=\"legend\"").unwrap()..];
+        assert!(legend.contains("\nclock\n") && legend.contains("\nsample\n"));
+    }
+
+    #[test]
+    fn zoom_composes_only_the_window() {
+        let diag = parse::from_json_str(r#"{"signals":[{"name":"A","wave":"lhlhlhlhlh","markers":[{"at":[1,4,8]}],
+            "ticks":["t0","t1","t2","t3","t4","t5","t6","t7","t8","t9"]}]}"#).unwrap();
+        let compose = |from: f64, to: f64| Compositor::default().zoom(from, to).cut_marks(true).compose(&diag).to_string();
+        let svg = compose(6.0, 2.0);
+        let style = Compositor::default().resolve_style(&diag).into_owned();
+        assert_eq!(view_box(&svg).0, 2.0 * style.padding + style.name_width + 4.0 * style.period_width);
+        assert!(svg.contains("url(#window)"));
+        for tick in 0..10 {
+            assert_eq!(svg.contains(&format!("\nt{}\n", tick)), (2..=6).contains(&tick), "t{}", tick);
+        }
+        let markers = &svg[svg.find("id=\"lane-0-markers\"").unwrap()..];
+        assert_eq!(markers[..markers.find("</g>").unwrap()].matches("<line").count(), 1);
+        // the cut marks are only shown where the diagram continues.
+        assert_eq!(svg.matches("\n…\n").count(), 2);
+        assert_eq!(compose(0.0, 6.0).matches("\n…\n").count(), 1);
+        assert_eq!(compose(0.0, 10.0).matches("\n…\n").count(), 0);
+    }
+
+    #[test]
+    fn split_lanes_keep_the_diagram_settings() {
+        let diag = parse::from_json_str(r#"{"config":{"title":"Bus","subtitle":"split","caption":"all lanes","style":"compact","theme":"dark"},
+            "signals":[{"name":"CLK/1","wave":"lhlhlhlh","node":".a.b.c"},{"name":"CLK 1","wave":"lh","node":".d"},{"name":"***","wave":"hl"}],
+            "edges":["a->b","c->d"]}"#).unwrap();
+        let splits = diag.split_lanes(false);
+        let names: Vec<&str> = splits.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["CLK_1", "CLK_1-2", "lane-3"]);
+        for (num, (_, lane)) in splits.iter().enumerate() {
+            assert_eq!(lane.lanes().iter().map(|l| l.signal.name.as_str()).collect::<Vec<_>>(), [diag.lanes()[num].signal.name.as_str()]);
+            assert_eq!((lane.title(), lane.subtitle(), lane.caption()), (String::new(), None, None));
+            assert_eq!((lane.style(), lane.theme()), (diag.style(), diag.theme()));
+        }
+        assert_eq!(splits.iter().map(|(_, lane)| lane.edges().len()).collect::<Vec<_>>(), [1, 0, 0]);
+
+        let mut comp = Compositor::default();
+        comp.split_title(true);
+        for (_, doc) in comp.compose_splits(&diag) {
+            let svg = doc.to_string();
+            assert!(svg.contains("\nBus\n") && svg.contains("id=\"arrowhead\"") && svg.contains(&Theme::dark().background.to_string()));
+            // the lanes keep the diagram's length.
+            let level_lines = &svg[svg.find("id=\"lane-0-level-lines\"").unwrap()..];
+            assert_eq!(attribute(level_lines, "x2") - attribute(level_lines, "x1"), 8.0 * Style::compact().period_width);
+        }
+    }
+}
@@ -0,0 +1,378 @@
+// compose/font.rs
+//
+// The bundled Tuffy font, used to make the text of composed documents independent of the installed fonts.
+// Tuffy is released into the public domain, see assets/fonts/Tuffy-LICENSE.txt.
+use std::collections::BTreeSet;
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use svg::node::element::path::Data;
+use subsetter::GlyphRemapper;
+use ttf_parser::{Face, GlyphId, OutlineBuilder, RawFace, Tag};
+
+use crate::model::marker::{TextAnchor, TextSize};
+
+/// The font file bundled with siggi.
+pub const FONT_DATA: &[u8] = include_bytes!("../../assets/fonts/Tuffy.ttf");
+/// The family name of the bundled font.
+pub const FONT_FAMILY: &str = "Tuffy";
+
+fn face() -> Face<'static> {
+    Face::parse(FONT_DATA, 0).expect("The bundled font is invalid")
+}
+
+/// Font size in pixels as used by browsers for the css keywords.
+pub fn font_size(size: TextSize) -> f64 {
+    match size {
+        TextSize::Small => 13.0,
+        TextSize::Smaller => 16.0 / 1.2,
+        TextSize::Normal => 16.0,
+        TextSize::Larger => 16.0 * 1.2,
+        TextSize::Large => 18.0,
+    }
+}
+
+/// Width in pixels of the text set in the bundled font with the given font size.
+pub fn text_width(text: &str, size: f64) -> f64 {
+    let face = face();
+    let glyphs = glyphs(&face, text);
+    let units: f64 = glyphs.iter().map(|g| g.1).sum();
+    units * size / face.units_per_em() as f64
+}
+
+/// Convert the text into outline path data with the given font size.
+/// The origin sits on the baseline at the text anchor.
+pub fn text_to_path(text: &str, size: f64, anchor: TextAnchor) -> Data {
+    let face = face();
+    let scale = size / face.units_per_em() as f64;
+    let glyphs = glyphs(&face, text);
+    let width: f64 = glyphs.iter().map(|g| g.1).sum();
+
+    let mut builder = PathBuilder {
+        data: Data::new(),
+        x: match anchor {
+            TextAnchor::Start => 0.0,
+            TextAnchor::Middle => -width / 2.0,
+            TextAnchor::End => -width,
+        },
+        scale,
+    };
+    for (glyph, advance) in glyphs {
+        face.outline_glyph(glyph, &mut builder);
+        builder.x += advance;
+    }
+    builder.data
+}
+
+/// A css `@font-face` rule embedding a subset of the bundled font, which contains only the given characters.
+pub fn font_face(chars: &BTreeSet<char>) -> String {
+    format!(
+        "@font-face {{ font-family: \"{}\"; src: url(\"data:font/ttf;base64,{}\") format(\"truetype\"); }}",
+        FONT_FAMILY, BASE64.encode(subset(chars)),
+    )
+}
+
+// Look up the glyphs of the text together with their kerned advance in font units.
+fn glyphs(face: &Face, text: &str) -> Vec<(GlyphId, f64)> {
+    let ids: Vec<GlyphId> = text.chars().map(|c| face.glyph_index(c).unwrap_or(GlyphId(0))).collect();
+    ids.iter().enumerate().map(|(idx, id)| {
+        let advance = face.glyph_hor_advance(*id).unwrap_or(0) as f64;
+        let kerning = ids.get(idx + 1).map(|next| kerning(face, *id, *next)).unwrap_or(0.0);
+        (*id, advance + kerning)
+    }).collect()
+}
+
+fn kerning(face: &Face, left: GlyphId, right: GlyphId) -> f64 {
+    face.tables().kern.and_then(|kern| {
+        kern.subtables.into_iter()
+            .filter(|st| st.horizontal && !st.variable)
+            .find_map(|st| st.glyphs_kerning(left, right))
+    }).unwrap_or(0) as f64
+}
+
+// Collects the glyph outlines into svg path data, flipping the y axis.
+struct PathBuilder {
+    data: Data,
+    x: f64,
+    scale: f64,
+}
+
+impl PathBuilder {
+    fn point(&self, x: f32, y: f32) -> (f64, f64) {
+        let round = |v: f64| (v * 100.0).round() / 100.0;
+        (round((self.x + x as f64) * self.scale), round(-(y as f64) * self.scale))
+    }
+}
+
+impl OutlineBuilder for PathBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.data = std::mem::take(&mut self.data).move_to(self.point(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.data = std::mem::take(&mut self.data).line_to(self.point(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (p1, p) = (self.point(x1, y1), self.point(x, y));
+        self.data = std::mem::take(&mut self.data).quadratic_curve_to((p1.0, p1.1, p.0, p.1));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (p1, p2, p) = (self.point(x1, y1), self.point(x2, y2), self.point(x, y));
+        self.data = std::mem::take(&mut self.data).cubic_curve_to((p1.0, p1.1, p2.0, p2.1, p.0, p.1));
+    }
+
+    fn close(&mut self) {
+        self.data = std::mem::take(&mut self.data).close();
+    }
+}
+
+// Build a font which only contains the glyphs of the given characters and the components of composite glyphs.
+// The subsetter renumbers the glyphs and leaves out the tables only browsers need,
+// so the character map, the OS/2 and the kerning table are added for the new glyph ids.
+fn subset(chars: &BTreeSet<char>) -> Vec<u8> {
+    let face = face();
+    let mut remapper = GlyphRemapper::new();
+    let mapping: Vec<(char, u16)> = chars.iter()
+        .filter_map(|c| face.glyph_index(*c).map(|id| (*c, remapper.remap(id.0))))
+        .collect();
+    let font = subsetter::subset(FONT_DATA, 0, &remapper).expect("The bundled font can't be subset");
+
+    let raw = RawFace::parse(&font, 0).expect("The subset is no valid font");
+    let mut tables: Vec<([u8; 4], Vec<u8>)> = raw.table_records.into_iter()
+        .filter_map(|record| raw.table(record.tag).map(|data| (record.tag.to_bytes(), data.to_vec())))
+        .collect();
+    tables.push((*b"cmap", cmap(&mapping)));
+    if let Some(os2) = face.raw_face().table(Tag::from_bytes(b"OS/2")) {
+        tables.push((*b"OS/2", os2.to_vec()));
+    }
+    let kern = kern(&face, &remapper);
+    if !kern.is_empty() {
+        tables.push((*b"kern", kern));
+    }
+    tables.sort_by_key(|t| t.0);
+    let mut font = write_font(&tables);
+
+    // the checksum adjustment makes the checksum of the whole font equal 0xB1B0AFBA.
+    if let Some(idx) = tables.iter().position(|t| &t.0 == b"head") {
+        let head_offset = read_u32(&font, 12 + idx * 16 + 8) as usize;
+        font[head_offset + 8..head_offset + 12].fill(0);
+        let adjustment = 0xB1B0AFBAu32.wrapping_sub(checksum(&font));
+        font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+// Character map with a format 4 subtable for the basic plane and a format 12 subtable for all characters.
+fn cmap(mapping: &[(char, u16)]) -> Vec<u8> {
+    // runs of characters mapped to consecutive glyphs as (first char, last char, first glyph).
+    let mut runs = Vec::<(u32, u32, u16)>::new();
+    for (c, id) in mapping {
+        match runs.last_mut() {
+            Some(run) if run.1 + 1 == *c as u32 && run.2 as u32 + run.1 - run.0 + 1 == *id as u32 => run.1 += 1,
+            _ => runs.push((*c as u32, *c as u32, *id)),
+        }
+    }
+
+    // the format 4 subtable ends with a segment for 0xFFFF.
+    let mut segments: Vec<(u16, u16, u16)> = runs.iter()
+        .filter(|run| run.1 < 0xFFFF)
+        .map(|run| (run.0 as u16, run.1 as u16, run.2.wrapping_sub(run.0 as u16)))
+        .collect();
+    segments.push((0xFFFF, 0xFFFF, 1));
+    let count = segments.len() as u16;
+    let entry_selector = 15 - count.leading_zeros() as u16;
+    let search_range = (1u16 << entry_selector) * 2;
+    let mut format4 = Vec::<u8>::new();
+    for value in [4, 16 + 8 * count, 0, count * 2, search_range, entry_selector, count * 2 - search_range] {
+        format4.extend(value.to_be_bytes());
+    }
+    format4.extend(segments.iter().flat_map(|s| s.1.to_be_bytes()));
+    format4.extend(0u16.to_be_bytes());
+    format4.extend(segments.iter().flat_map(|s| s.0.to_be_bytes()));
+    format4.extend(segments.iter().flat_map(|s| s.2.to_be_bytes()));
+    format4.extend(segments.iter().flat_map(|_| 0u16.to_be_bytes()));
+
+    let mut format12 = Vec::<u8>::new();
+    format12.extend(12u16.to_be_bytes());
+    format12.extend(0u16.to_be_bytes());
+    for value in [16 + 12 * runs.len() as u32, 0, runs.len() as u32] {
+        format12.extend(value.to_be_bytes());
+    }
+    format12.extend(runs.iter().flat_map(|run| [run.0, run.1, run.2 as u32]).flat_map(u32::to_be_bytes));
+
+    // version, number of subtables and the windows unicode records pointing to them.
+    let mut cmap = Vec::<u8>::new();
+    for value in [0u16, 2, 3, 1] {
+        cmap.extend(value.to_be_bytes());
+    }
+    cmap.extend(20u32.to_be_bytes());
+    cmap.extend(3u16.to_be_bytes());
+    cmap.extend(10u16.to_be_bytes());
+    cmap.extend((20 + format4.len() as u32).to_be_bytes());
+    cmap.extend(format4);
+    cmap.extend(format12);
+    cmap
+}
+
+// Kerning table with the kerning of all pairs of kept glyphs, empty if none of them are kerned.
+fn kern(face: &Face, remapper: &GlyphRemapper) -> Vec<u8> {
+    let glyphs: Vec<u16> = remapper.remapped_gids().collect();
+    let mut pairs = Vec::<(u16, u16, i16)>::new();
+    for (left, old_left) in glyphs.iter().enumerate() {
+        for (right, old_right) in glyphs.iter().enumerate() {
+            let value = kerning(face, GlyphId(*old_left), GlyphId(*old_right));
+            if value != 0.0 {
+                pairs.push((left as u16, right as u16, value as i16));
+            }
+        }
+    }
+    // the length of the subtable is stored in 16 bits.
+    pairs.truncate((u16::MAX as usize - 14) / 6);
+    if pairs.is_empty() {
+        return Vec::new();
+    }
+
+    let count = pairs.len() as u16;
+    let entry_selector = 15 - count.leading_zeros() as u16;
+    let search_range = (1u16 << entry_selector) * 6;
+    let mut kern = Vec::<u8>::new();
+    // version and number of subtables, followed by a horizontal format 0 subtable.
+    for value in [0, 1, 0, 14 + 6 * count, 1, count, search_range, entry_selector, count * 6 - search_range] {
+        kern.extend(value.to_be_bytes());
+    }
+    for (left, right, value) in pairs {
+        kern.extend(left.to_be_bytes());
+        kern.extend(right.to_be_bytes());
+        kern.extend(value.to_be_bytes());
+    }
+    kern
+}
+
+// Write the tables (sorted by tag) into a TrueType font file.
+fn write_font(tables: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let count = tables.len() as u16;
+    let entry_selector = 15 - count.leading_zeros() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+
+    let mut font = Vec::<u8>::new();
+    font.extend(0x00010000u32.to_be_bytes());
+    font.extend(count.to_be_bytes());
+    font.extend(search_range.to_be_bytes());
+    font.extend(entry_selector.to_be_bytes());
+    font.extend((count * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, data) in tables {
+        font.extend(tag.iter());
+        font.extend(checksum(data).to_be_bytes());
+        font.extend((offset as u32).to_be_bytes());
+        font.extend((data.len() as u32).to_be_bytes());
+        offset += (data.len() + 3) & !3;
+    }
+    for (_, data) in tables {
+        font.extend(data);
+        font.resize((font.len() + 3) & !3, 0);
+    }
+    font
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn read_u32(data: &[u8], pos: usize) -> u32 {
+    data.get(pos..pos + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]])).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Counts the segments of a glyph outline.
+    struct Segments(usize);
+
+    impl OutlineBuilder for Segments {
+        fn move_to(&mut self, _: f32, _: f32) { self.0 += 1 }
+        fn line_to(&mut self, _: f32, _: f32) { self.0 += 1 }
+        fn quad_to(&mut self, _: f32, _: f32, _: f32, _: f32) { self.0 += 1 }
+        fn curve_to(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32) { self.0 += 1 }
+        fn close(&mut self) {}
+    }
+
+    fn has_outline(face: &Face, c: char) -> bool {
+        let mut segments = Segments(0);
+        let id = face.glyph_index(c).expect("The character is missing in the font");
+        face.outline_glyph(id, &mut segments).is_some() && segments.0 > 0
+    }
+
+    #[test]
+    fn subset_keeps_only_the_used_outlines() {
+        let kept: BTreeSet<char> = "Siggi 10ns…".chars().collect();
+        let font = subset(&kept);
+        let face = Face::parse(&font, 0).expect("The subset is no valid font");
+        assert_eq!(face.number_of_glyphs() as usize, kept.len() + 1);
+        for c in kept.iter().filter(|c| !c.is_whitespace()) {
+            assert!(has_outline(&face, *c), "'{}' lost its outline", c);
+        }
+        for c in ['A', 'z', '7'] {
+            assert!(face.glyph_index(c).is_none(), "'{}' wasn't removed", c);
+        }
+        for table in [b"OS/2", b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"post"] {
+            assert!(face.raw_face().table(Tag::from_bytes(table)).is_some(), "{:?} is missing", std::str::from_utf8(table));
+        }
+        assert_eq!(checksum(&font), 0xB1B0AFBA);
+    }
+
+    #[test]
+    fn subset_keeps_the_kerning_and_advances() {
+        let text = "AVTo";
+        let font = subset(&text.chars().collect());
+        let face = Face::parse(&font, 0).unwrap();
+        let original = super::face();
+        for c in text.chars() {
+            assert_eq!(face.glyph_hor_advance(face.glyph_index(c).unwrap()), original.glyph_hor_advance(original.glyph_index(c).unwrap()));
+        }
+        let pair = |face: &Face, left, right| kerning(face, face.glyph_index(left).unwrap(), face.glyph_index(right).unwrap());
+        assert_ne!(pair(&original, 'A', 'V'), 0.0);
+        assert_eq!(pair(&face, 'A', 'V'), pair(&original, 'A', 'V'));
+        assert_eq!(pair(&face, 'T', 'o'), pair(&original, 'T', 'o'));
+    }
+
+    #[test]
+    fn subset_keeps_the_components_of_composite_glyphs() {
+        let face = super::face();
+        let composite = ('\u{a0}'..'\u{17f}').find(|c| is_composite(&face, *c))
+            .expect("The font has no composite glyphs");
+        let font = subset(&BTreeSet::from([composite]));
+        let subset_face = Face::parse(&font, 0).unwrap();
+        assert!(has_outline(&subset_face, composite));
+        assert!(subset_face.number_of_glyphs() > 2);
+    }
+
+    // Composite glyphs have a negative number of contours.
+    fn is_composite(face: &Face, c: char) -> bool {
+        let raw = face.raw_face();
+        let head = raw.table(Tag::from_bytes(b"head")).unwrap();
+        let loca = raw.table(Tag::from_bytes(b"loca")).unwrap();
+        let glyf = raw.table(Tag::from_bytes(b"glyf")).unwrap();
+        let read_u16 = |data: &[u8], pos: usize| u16::from_be_bytes([data[pos], data[pos + 1]]);
+        let id = match face.glyph_index(c) {
+            Some(id) => id.0 as usize,
+            None => return false,
+        };
+        let offset = if read_u16(head, 50) == 1 { read_u32(loca, id * 4) as usize } else { read_u16(loca, id * 2) as usize * 2 };
+        glyf.len() >= offset + 2 && (read_u16(glyf, offset) as i16) < 0
+    }
+
+    #[test]
+    fn text_width_grows_with_text_and_size() {
+        assert_eq!(text_width("", 16.0), 0.0);
+        assert!(text_width("ab", 16.0) > text_width("a", 16.0));
+        assert!((text_width("abc", 32.0) - 2.0 * text_width("abc", 16.0)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,166 @@
+// compose/text.rs
+//
+// Render diagrams as plain text waveforms for terminal previews or code comments.
+use crate::model::{Diagram, Lane, signal::Level, marker::{Label, TextAnchor}};
+
+use super::Backend;
+
+/// The characters used to draw the waves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Charset {
+    /// Unicode box drawing characters like `▁▔╱╲`.
+    #[default]
+    Unicode,
+    /// Plain ascii characters like `_-/\`.
+    Ascii,
+}
+
+struct Glyphs {
+    low: char,
+    high: char,
+    idle: char,
+    rise: char,
+    fall: char,
+    marker: char,
+    axis: char,
+    arrow: char,
+}
+
+impl Charset {
+    fn glyphs(&self) -> Glyphs {
+        match self {
+            Charset::Unicode => Glyphs { low: '▁', high: '▔', idle: '─', rise: '╱', fall: '╲', marker: '┊', axis: '─', arrow: '▶' },
+            Charset::Ascii => Glyphs { low: '_', high: '-', idle: '=', rise: '/', fall: '\\', marker: '|', axis: '-', arrow: '>' },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TextCompositor {
+    /// Number of characters used to draw one period.
+    pub period_width: usize,
+    pub charset: Charset,
+}
+
+impl Default for TextCompositor {
+    fn default() -> Self {
+        Self { period_width: 4, charset: Charset::default() }
+    }
+}
+
+impl TextCompositor {
+    pub fn new() -> Self { Self::default() }
+
+    /// Set the number of characters per period.
+    /// Supports builder pattern
+    pub fn period_width(mut self, width: usize) -> Self {
+        self.period_width = width.max(2);
+        self
+    }
+
+    /// Use plain ascii characters.
+    /// Supports builder pattern
+    pub fn ascii(mut self) -> Self {
+        self.charset = Charset::Ascii;
+        self
+    }
+
+    pub fn compose(&self, diag: &Diagram) -> String {
+        let name_width = diag.lanes().iter().map(|l| l.signal.name.chars().count()).max().unwrap_or(0);
+        let offset = if name_width > 0 { name_width + 2 } else { 0 };
+        let wave_width = diag.lanes().iter().map(|l| l.signal.len()).max().unwrap_or(0) as usize * self.period_width;
+
+        let mut lines = Vec::<String>::new();
+
+        if !diag.title().is_empty() {
+            lines.push(center(&diag.title(), offset + wave_width));
+            lines.push(String::new());
+        }
+
+        for lane in diag.lanes() {
+            lines.extend(self.compose_lane(lane, offset, wave_width));
+        }
+
+        if let Some(xaxis) = diag.xaxis() {
+            let glyphs = self.charset.glyphs();
+            let mut axis: String = std::iter::repeat_n(glyphs.axis, wave_width.saturating_sub(1)).collect();
+            axis.push(glyphs.arrow);
+            lines.push(format!("{}{}", " ".repeat(offset), axis));
+            lines.push(format!("{}{:>width$}", " ".repeat(offset), xaxis, width = wave_width));
+        }
+
+        lines.iter().map(|l| l.trim_end()).collect::<Vec<_>>().join("\n")
+    }
+
+    fn compose_lane(&self, lane: &Lane, offset: usize, wave_width: usize) -> Vec<String> {
+        let glyphs = self.charset.glyphs();
+        let mut lines = Vec::<String>::new();
+
+        if !lane.markers.is_empty() {
+            let mut row = vec![' '; wave_width + 1];
+            for marker in lane.markers.iter() {
+                if let Some(cell) = row.get_mut(self.column(marker.position)) {
+                    *cell = glyphs.marker;
+                }
+            }
+            lines.push(format!("{}{}", " ".repeat(offset), row.iter().collect::<String>()));
+        }
+
+        // sample the signal in the middle of each character.
+        let mut wave = String::new();
+        let mut prev: Option<Level> = None;
+        for column in 0..wave_width {
+            let level = lane.signal.level_at((column as f64 + 0.5) / self.period_width as f64);
+            wave.push(match (prev, level) {
+                (Some(Level::Low), Level::High) => glyphs.rise,
+                (Some(Level::High), Level::Low) => glyphs.fall,
+                (_, Level::High) => glyphs.high,
+                (_, Level::Low) => glyphs.low,
+                _ => glyphs.idle,
+            });
+            prev = Some(level);
+        }
+        lines.push(format!("{:<width$}{}", lane.signal.name, wave, width = offset));
+
+        if !lane.labels.is_empty() {
+            let mut row = vec![' '; wave_width + 1];
+            for label in lane.labels.iter() {
+                self.place_label(&mut row, label);
+            }
+            lines.push(format!("{}{}", " ".repeat(offset), row.iter().collect::<String>()));
+        }
+
+        lines
+    }
+
+    fn column(&self, position: f64) -> usize {
+        (position * self.period_width as f64).round().max(0.0) as usize
+    }
+
+    fn place_label(&self, row: &mut Vec<char>, label: &Label) {
+        let text: Vec<char> = label.text.chars().collect();
+        let column = self.column(label.position);
+        let start = match label.anchor {
+            TextAnchor::Start => column,
+            TextAnchor::Middle => column.saturating_sub(text.len() / 2),
+            TextAnchor::End => column.saturating_sub(text.len()),
+        };
+        if row.len() < start + text.len() {
+            row.resize(start + text.len(), ' ');
+        }
+        row[start..start + text.len()].copy_from_slice(&text);
+    }
+}
+
+impl Backend for TextCompositor {
+    type Output = String;
+
+    fn render(&self, diag: &Diagram) -> Self::Output {
+        self.compose(diag)
+    }
+}
+
+fn center(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    format!("{}{}", " ".repeat(width.saturating_sub(len) / 2), text)
+}
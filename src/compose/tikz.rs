@@ -0,0 +1,191 @@
+// compose/tikz.rs
+//
+// Render diagrams as TikZ pictures, so they inherit the fonts of the LaTeX document.
+use std::{collections::BTreeSet, fmt::Write};
+
+use crate::model::{Diagram, Lane, Theme, utils::Color, signal::Level, marker::{Label, TextAnchor, TextSize}};
+
+use super::Backend;
+
+// All coordinates are given in periods (x) and wave heights (y).
+const LANE_HEIGHT: f64 = 2.0;
+const NAME_OFFSET: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TikzCompositor {
+    /// Width of one period in cm.
+    pub period_width: f64,
+    /// Height of a wave in cm.
+    pub wave_height: f64,
+    /// Wrap the picture into a standalone LaTeX document.
+    pub standalone: bool,
+}
+
+impl Default for TikzCompositor {
+    fn default() -> Self {
+        Self { period_width: 1.0, wave_height: 0.6, standalone: false }
+    }
+}
+
+impl TikzCompositor {
+    pub fn new() -> Self { Self::default() }
+
+    /// Wrap the picture into a standalone LaTeX document.
+    /// Supports builder pattern
+    pub fn standalone(mut self, standalone: bool) -> Self {
+        self.standalone = standalone;
+        self
+    }
+
+    /// Set the size of one period in cm.
+    /// Supports builder pattern
+    pub fn scale(mut self, period_width: f64, wave_height: f64) -> Self {
+        self.period_width = period_width;
+        self.wave_height = wave_height;
+        self
+    }
+
+    pub fn compose(&self, diag: &Diagram) -> String {
+        let wave_len = diag.lanes().iter().map(|l| l.signal.len()).max().unwrap_or(0) as f64;
+        let bottom = -(diag.lane_count() as f64) * LANE_HEIGHT;
+        let theme = diag.theme();
+        let mut out = String::new();
+
+        if self.standalone {
+            out.push_str("\\documentclass[tikz]{standalone}\n\\begin{document}\n");
+        }
+
+        for hex in used_colors(diag) {
+            writeln!(out, "\\definecolor{{siggi-{hex}}}{{HTML}}{{{hex}}}", hex = hex).unwrap();
+        }
+
+        writeln!(out, "\\begin{{tikzpicture}}[x={}cm, y={}cm]", self.period_width, self.wave_height).unwrap();
+
+        if diag.background() != Color::White {
+            writeln!(out, "\\fill[{}] (-3, {}) rectangle ({}, 2);", color_name(diag.background()), bottom - 1.5, wave_len + 1.0).unwrap();
+        }
+
+        if !diag.title().is_empty() {
+            writeln!(out, "\\node[anchor=south, text={}] at ({}, 0.5) {{{}}};", color_name(theme.text), wave_len / 2.0, escape(&diag.title())).unwrap();
+        }
+
+        for (num, lane) in diag.lanes().iter().enumerate() {
+            // the wave's low level of each lane sits at y, the high level at y + 1.
+            let y = -(num as f64 + 1.0) * LANE_HEIGHT + 0.5;
+            writeln!(out, "% lane-{}", num).unwrap();
+            self.compose_lane(&mut out, lane, y, wave_len, &theme);
+        }
+
+        if let Some(xaxis) = diag.xaxis() {
+            writeln!(out, "\\draw[->, {}] (0, {y}) -- ({}, {y}) node[below left, font=\\small] {{{}}};",
+                color_name(theme.guide), wave_len, escape(xaxis), y = bottom).unwrap();
+        }
+
+        out.push_str("\\end{tikzpicture}\n");
+
+        if self.standalone {
+            out.push_str("\\end{document}\n");
+        }
+        out
+    }
+
+    fn compose_lane(&self, out: &mut String, lane: &Lane, y: f64, wave_len: f64, theme: &Theme) {
+        let guide = color_name(theme.guide);
+        let signal = &lane.signal;
+
+        writeln!(out, "\\node[anchor=east, text={}] at (-{}, {}) {{{}}};", color_name(theme.text_color(signal.color)), NAME_OFFSET * 2.0, y + 0.5, escape(&signal.name)).unwrap();
+        writeln!(out, "\\node[anchor=east, text={}, font=\\scriptsize] at (-0.1, {}) {{{}}};", guide, y + 1.0, escape(&signal.y_axis.0)).unwrap();
+        writeln!(out, "\\node[anchor=east, text={}, font=\\scriptsize] at (-0.1, {}) {{{}}};", guide, y, escape(&signal.y_axis.1)).unwrap();
+
+        for level in [0.0, 0.5, 1.0] {
+            writeln!(out, "\\draw[{}, dashed, very thin] (0, {y}) -- ({}, {y});", guide, wave_len, y = y + level).unwrap();
+        }
+
+        let level_y = |level: Level| y + match level {
+            Level::High => 1.0,
+            Level::Idle => 0.5,
+            _ => 0.0,
+        };
+        let wave_end = signal.end();
+        let mut points = Vec::<(f64, f64)>::new();
+        for (time, level) in signal.transitions() {
+            if let Some((_, prev_y)) = points.last().copied() {
+                points.push((time, prev_y));
+            }
+            points.push((time, level_y(level)));
+        }
+        if let Some((_, last_y)) = points.last().copied() {
+            points.push((wave_end, last_y));
+        }
+        let path = points.iter().map(|(x, y)| format!("({}, {})", x, y)).collect::<Vec<_>>().join(" -- ");
+        writeln!(out, "\\draw[{}, very thick, line join=round, line cap=round] {};", color_name(theme.signal_color(signal.color)), path).unwrap();
+
+        for marker in lane.markers.iter() {
+            writeln!(out, "\\draw[{}{}, line width={}pt] ({x}, {}) -- ({x}, {});",
+                color_name(theme.guide_color(marker.color)), if marker.dashed { ", dashed" } else { "" }, marker.thickness * 0.75, y - 0.25, y + 1.25, x = marker.position).unwrap();
+        }
+
+        for label in lane.labels.iter() {
+            writeln!(out, "{}", compose_label(label, y - 0.5, theme)).unwrap();
+        }
+    }
+}
+
+impl Backend for TikzCompositor {
+    type Output = String;
+
+    fn render(&self, diag: &Diagram) -> Self::Output {
+        self.compose(diag)
+    }
+}
+
+fn compose_label(label: &Label, y: f64, theme: &Theme) -> String {
+    let anchor = match label.anchor {
+        TextAnchor::Start => "west",
+        TextAnchor::Middle => "center",
+        TextAnchor::End => "east",
+    };
+    let font = match label.size {
+        TextSize::Small => ", font=\\small",
+        TextSize::Smaller => ", font=\\footnotesize",
+        TextSize::Normal => "",
+        TextSize::Larger => ", font=\\large",
+        TextSize::Large => ", font=\\Large",
+    };
+    format!("\\node[anchor={}, text={}{}] at ({}, {}) {{{}}};", anchor, color_name(theme.text_color(label.color)), font, label.position, y, escape(&label.text))
+}
+
+// Collect the hex values of all colors used by the diagram to define them up front.
+fn used_colors(diag: &Diagram) -> BTreeSet<String> {
+    let theme = diag.theme();
+    let mut colors = vec![theme.guide, theme.text, diag.background()];
+    for lane in diag.lanes() {
+        colors.push(theme.text_color(lane.signal.color));
+        colors.push(theme.signal_color(lane.signal.color));
+        colors.extend(lane.markers.iter().map(|m| theme.guide_color(m.color)));
+        colors.extend(lane.labels.iter().map(|l| theme.text_color(l.color)));
+    }
+    colors.iter().map(|c| c.to_string().trim_start_matches('#').to_string()).collect()
+}
+
+// LaTeX color names are derived from the hex value, e.g. siggi-FF5733.
+fn color_name(color: Color) -> String {
+    format!("siggi-{}", color.to_string().trim_start_matches('#'))
+}
+
+fn escape(text: &str) -> String {
+    let mut result = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => result.push_str("\\textbackslash{}"),
+            '~' => result.push_str("\\textasciitilde{}"),
+            '^' => result.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                result.push('\\');
+                result.push(c);
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
@@ -0,0 +1,170 @@
+// compose/vcd.rs
+//
+// Write diagrams as value change dump, e.g. to view them in GTKWave.
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::model::{Diagram, signal::Level};
+
+use super::Backend;
+
+// Resolutions tried to place all transitions on an integer time step.
+const STEPS_PER_PERIOD: [u32; 10] = [1, 2, 4, 5, 10, 20, 25, 50, 100, 1000];
+// Units of the timescale, from large to small.
+const TIME_UNITS: [(f64, &str); 6] = [(1.0, "s"), (1e-3, "ms"), (1e-6, "us"), (1e-9, "ns"), (1e-12, "ps"), (1e-15, "fs")];
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VcdCompositor {
+    /// Number of time steps per period. Gets derived from the signal's periods and phases if None.
+    pub steps_per_period: Option<u32>,
+}
+
+impl VcdCompositor {
+    pub fn new() -> Self { Self::default() }
+
+    /// Use a fixed number of time steps per period.
+    /// Supports builder pattern
+    pub fn steps_per_period(mut self, steps: u32) -> Self {
+        self.steps_per_period = Some(steps.max(1));
+        self
+    }
+
+    pub fn compose(&self, diag: &Diagram) -> String {
+        let transitions: Vec<Vec<(f64, Level)>> = diag.lanes().iter().map(|l| l.signal.transitions()).collect();
+        let steps = self.steps_per_period.unwrap_or_else(|| find_steps_per_period(&transitions));
+        // without a timebase each step takes 1ns.
+        let (timescale, step_len) = diag.timebase().map(|timebase| timescale(timebase / steps as f64)).unwrap_or(("1ns".to_string(), 1));
+        let end = diag.lanes().iter().map(|l| l.signal.end()).fold(0.0, f64::max);
+        let end = (end * steps as f64 - 1e-6).ceil().max(0.0) as u64 * step_len;
+
+        let mut out = String::new();
+        writeln!(out, "$version siggi {} $end", env!("CARGO_PKG_VERSION")).unwrap();
+        writeln!(out, "$comment one period equals {} time steps $end", steps as u64 * step_len).unwrap();
+        writeln!(out, "$timescale {} $end", timescale).unwrap();
+        writeln!(out, "$scope module {} $end", identifier_name(&diag.title(), "siggi")).unwrap();
+        for (num, lane) in diag.lanes().iter().enumerate() {
+            writeln!(out, "$var wire 1 {} {} $end", var_id(num), identifier_name(&lane.signal.name, &format!("lane_{}", num))).unwrap();
+        }
+        writeln!(out, "$upscope $end").unwrap();
+        writeln!(out, "$enddefinitions $end").unwrap();
+
+        // collect all value changes ordered by time.
+        let mut changes = BTreeMap::<u64, Vec<String>>::new();
+        for (num, transitions) in transitions.iter().enumerate() {
+            for (time, level) in transitions {
+                let step = (time * steps as f64).round() as u64 * step_len;
+                changes.entry(step).or_default().push(format!("{}{}", value(*level), var_id(num)));
+            }
+        }
+
+        for (step, values) in changes.iter() {
+            writeln!(out, "#{}", step).unwrap();
+            if *step == 0 {
+                writeln!(out, "$dumpvars").unwrap();
+            }
+            for value in values {
+                writeln!(out, "{}", value).unwrap();
+            }
+            if *step == 0 {
+                writeln!(out, "$end").unwrap();
+            }
+        }
+
+        if changes.keys().last().is_none_or(|last| *last < end) {
+            writeln!(out, "#{}", end).unwrap();
+        }
+        out
+    }
+}
+
+impl Backend for VcdCompositor {
+    type Output = String;
+
+    fn render(&self, diag: &Diagram) -> Self::Output {
+        self.compose(diag)
+    }
+}
+
+// Find the smallest resolution which puts all transitions on a time step.
+fn find_steps_per_period(transitions: &[Vec<(f64, Level)>]) -> u32 {
+    STEPS_PER_PERIOD.iter().copied().find(|steps| {
+        transitions.iter().flatten().all(|(time, _)| {
+            let scaled = time * *steps as f64;
+            (scaled - scaled.round()).abs() < 1e-6
+        })
+    }).unwrap_or(*STEPS_PER_PERIOD.last().unwrap())
+}
+
+// Find the largest timescale (1, 10 or 100 of a unit) which fits a whole number of times into a step.
+// Returns the timescale and the length of a step in it.
+fn timescale(step: f64) -> (String, u64) {
+    for (factor, unit) in TIME_UNITS {
+        for magnitude in [100.0, 10.0, 1.0] {
+            let len = step / (factor * magnitude);
+            if len >= 1.0 - 1e-9 && (len - len.round()).abs() < 1e-6 * len {
+                return (format!("{}{}", magnitude, unit), len.round() as u64);
+            }
+        }
+    }
+    ("1fs".to_string(), (step / 1e-15).round().max(1.0) as u64)
+}
+
+fn value(level: Level) -> char {
+    match level {
+        Level::High => '1',
+        Level::Idle => 'z',
+        _ => '0',
+    }
+}
+
+// Identifiers are built from the printable ascii characters ! to ~.
+fn var_id(mut num: usize) -> String {
+    let mut id = String::new();
+    loop {
+        id.push((b'!' + (num % 94) as u8) as char);
+        num /= 94;
+        if num == 0 {
+            return id;
+        }
+        num -= 1;
+    }
+}
+
+// Variable and scope names must not contain whitespace.
+fn identifier_name(name: &str, fallback: &str) -> String {
+    let name: String = name.split_whitespace().collect::<Vec<_>>().join("_");
+    if name.is_empty() { fallback.to_string() } else { name }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn period_and_end_in_timescale_units() {
+        let diag = parse::from_json_str(r#"{"config":{"timebase":"10ns"},"signals":[{"name":"A","wave":"lhl","phase":0.5}]}"#).unwrap();
+        let vcd = VcdCompositor::default().compose(&diag);
+        assert!(vcd.contains("$comment one period equals 10 time steps $end"));
+        assert!(vcd.contains("$timescale 1ns $end"));
+        assert!(vcd.ends_with("#35\n"));
+    }
+
+    #[test]
+    fn largest_fitting_timescale() {
+        let cases = [(1e-9, "1ns", 1), (5e-9, "1ns", 5), (1e-8, "10ns", 1), (2.5e-7, "10ns", 25), (1e-6, "1us", 1), (3e-4, "100us", 3), (2.0, "1s", 2), (1e-15, "1fs", 1), (4e-16, "1fs", 1)];
+        for (step, scale, len) in cases {
+            assert_eq!(timescale(step), (scale.to_string(), len), "step {}", step);
+        }
+    }
+
+    #[test]
+    fn steps_per_period_fit_all_transitions() {
+        let at = |times: &[f64]| vec![times.iter().map(|t| (*t, Level::High)).collect::<Vec<_>>()];
+        assert_eq!(find_steps_per_period(&at(&[0.0, 1.0, 7.0])), 1);
+        assert_eq!(find_steps_per_period(&at(&[0.5, 3.0])), 2);
+        assert_eq!(find_steps_per_period(&at(&[0.25, 0.5])), 4);
+        assert_eq!(find_steps_per_period(&at(&[0.2, 0.5])), 10);
+        assert_eq!(find_steps_per_period(&at(&[0.001])), 1000);
+        assert_eq!(find_steps_per_period(&at(&[1.0 / 3.0])), 1000);
+    }
+}
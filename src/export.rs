@@ -0,0 +1,36 @@
+// export.rs
+//
+// Convert composed svg documents into other file formats.
+pub mod error;
+pub mod png;
+pub mod pdf;
+pub mod html;
+
+use std::sync::Arc;
+
+use resvg::usvg;
+
+use crate::compose::font;
+
+use self::error::ExportError;
+
+// Parse a composed document into an usvg tree, which is used by all raster and vector exports.
+fn to_usvg_tree(doc: &svg::Document) -> Result<usvg::Tree, ExportError> {
+    let options = usvg::Options { fontdb: Arc::new(system_fonts()), ..Default::default() };
+
+    Ok(usvg::Tree::from_str(&doc.to_string(), &options)?)
+}
+
+// Load the system fonts and the bundled font. usvg falls back to the serif family for missing fonts,
+// so point it to the bundled font if no serif font is installed.
+fn system_fonts() -> usvg::fontdb::Database {
+    let mut fontdb = usvg::fontdb::Database::new();
+    fontdb.load_system_fonts();
+    fontdb.load_font_data(font::FONT_DATA.to_vec());
+
+    let query = usvg::fontdb::Query { families: &[usvg::fontdb::Family::Serif], ..Default::default() };
+    if fontdb.query(&query).is_none() {
+        fontdb.set_serif_family(font::FONT_FAMILY);
+    }
+    fontdb
+}
@@ -0,0 +1,46 @@
+// Errors
+
+use std::{fmt, error::Error, io};
+
+use resvg::usvg;
+
+#[derive(Debug)]
+pub enum ExportError {
+    SvgError(usvg::Error),
+    PdfError(svg2pdf::ConversionError),
+    RenderError,
+    EncodeError(String),
+    IoError(io::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::SvgError(err) => write!(f," Error occured while reading the svg: {}", err),
+            ExportError::PdfError(err) => write!(f," Error occured during pdf conversion: {}", err),
+            ExportError::RenderError => write!(f," Error occured during rendering"),
+            ExportError::EncodeError(msg) => write!(f," Error occured during encoding: {}", msg),
+            ExportError::IoError(err) => write!(f," Error occured while writing the file: {}", err),
+        }
+    }
+}
+
+impl Error for ExportError {}
+
+impl From<usvg::Error> for ExportError {
+    fn from(err: usvg::Error) -> Self {
+        ExportError::SvgError(err)
+    }
+}
+
+impl From<svg2pdf::ConversionError> for ExportError {
+    fn from(err: svg2pdf::ConversionError) -> Self {
+        ExportError::PdfError(err)
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(err: io::Error) -> Self {
+        ExportError::IoError(err)
+    }
+}
@@ -0,0 +1,249 @@
+// export/html.rs
+//
+// Wrap a composed diagram into an html page with a small script to inspect the waves:
+// hovering shows the level and time, clicks set measurement cursors and lanes can be hidden.
+use std::{fs, path::Path};
+
+use serde::Serialize;
+
+use crate::{compose::{escape, Backend, Compositor, WaveGeometry}, model::{Diagram, signal::Level, axis}};
+
+use super::error::ExportError;
+
+#[derive(Serialize)]
+struct HtmlData {
+    rows: Vec<HtmlRow>,
+    lane_tops: Vec<f64>,
+    period_width: f64,
+    lane_height: f64,
+    /// Factor and symbol of the unit used to show times, which are given in periods without a timebase.
+    unit: Option<(f64, &'static str)>,
+    timebase: Option<f64>,
+    lanes: Vec<HtmlLane>,
+}
+
+#[derive(Serialize)]
+struct HtmlRow {
+    x: f64,
+    y: f64,
+    start: f64,
+    end: f64,
+    id_prefix: String,
+}
+
+#[derive(Serialize)]
+struct HtmlLane {
+    name: String,
+    /// (time, level) pairs with the levels "H", "L" or "Z".
+    transitions: Vec<(f64, &'static str)>,
+}
+
+/// Compose the diagram and wrap it into an interactive html page.
+pub fn to_html(diag: &Diagram, comp: &Compositor) -> String {
+    let doc = comp.compose(diag);
+    let WaveGeometry { rows, lane_tops, period_width, lane_height, .. } = comp.geometry(diag);
+
+    let data = HtmlData {
+        rows: rows.into_iter().map(|row| HtmlRow { x: row.x, y: row.y, start: row.start, end: row.end, id_prefix: row.id_prefix }).collect(),
+        lane_tops, period_width, lane_height,
+        unit: diag.timebase().map(axis::time_unit),
+        timebase: diag.timebase(),
+        lanes: diag.lanes().iter().map(|lane| HtmlLane {
+            name: lane.signal.name.clone(),
+            transitions: lane.signal.transitions().iter().map(|(time, level)| (*time, match level {
+                Level::High => "H",
+                Level::Idle => "Z",
+                _ => "L",
+            })).collect(),
+        }).collect(),
+    };
+    // Make sure the json can't close the script tag.
+    let data = serde_json::to_string(&data).expect("Could not serialize the diagram data").replace("</", "<\\/");
+
+    TEMPLATE
+        .replace("{{title}}", &escape(&diag.title()))
+        .replace("{{data}}", &data)
+        .replace("{{svg}}", &doc.to_string())
+}
+
+/// Compose the diagram and save it as interactive html page.
+pub fn save<P: AsRef<Path>>(path: P, diag: &Diagram, comp: &Compositor) -> Result<(), ExportError> {
+    fs::write(path, to_html(diag, comp))?;
+    Ok(())
+}
+
+/// Backend rendering interactive html pages with the svg of the given compositor.
+pub struct HtmlCompositor<'a> {
+    comp: &'a Compositor,
+}
+
+impl<'a> HtmlCompositor<'a> {
+    pub fn new(comp: &'a Compositor) -> Self { Self { comp } }
+}
+
+impl Backend for HtmlCompositor<'_> {
+    type Output = String;
+
+    fn render(&self, diag: &Diagram) -> Self::Output {
+        to_html(diag, self.comp)
+    }
+}
+
+const TEMPLATE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{{title}}</title>
+<style>
+  body { font-family: sans-serif; margin: 1em; }
+  #siggi { position: relative; display: inline-block; }
+  #siggi svg { display: block; max-width: 100%; height: auto; cursor: crosshair; }
+  #siggi-tooltip { position: absolute; display: none; pointer-events: none; padding: 2px 6px;
+    background: rgba(0, 0, 0, 0.75); color: white; border-radius: 3px; font-size: small; white-space: nowrap; }
+  #siggi-controls { margin-top: 0.5em; font-size: small; }
+  #siggi-controls label { margin-right: 1em; }
+</style>
+</head>
+<body>
+<div id="siggi">
+{{svg}}
+<div id="siggi-tooltip"></div>
+</div>
+<div id="siggi-controls">
+  <div id="siggi-lanes"></div>
+  <div id="siggi-cursors">Click into the diagram to set measurement cursors.</div>
+</div>
+<script>
+(function () {
+  const data = {{data}};
+  const NS = "http://www.w3.org/2000/svg";
+  const container = document.getElementById("siggi");
+  const svg = container.querySelector("svg");
+  const tooltip = document.getElementById("siggi-tooltip");
+  const cursorInfo = document.getElementById("siggi-cursors");
+  const hidden = data.lanes.map(() => false);
+  const overlay = document.createElementNS(NS, "g");
+  svg.appendChild(overlay);
+  let cursors = [];
+
+  function toSvg(evt) {
+    const pt = svg.createSVGPoint();
+    pt.x = evt.clientX;
+    pt.y = evt.clientY;
+    return pt.matrixTransform(svg.getScreenCTM().inverse());
+  }
+
+  const lanesHeight = data.lane_tops.length ? data.lane_tops[data.lane_tops.length - 1] + data.lane_height : 0;
+
+  // Find the row under the point together with the time at the point.
+  function rowAt(pt) {
+    for (const row of data.rows) {
+      const time = (pt.x - row.x) / data.period_width;
+      if (pt.y >= row.y && pt.y < row.y + lanesHeight && time >= row.start && time <= row.end) {
+        return { row, time };
+      }
+    }
+    return null;
+  }
+
+  function laneAt(row, y) {
+    const num = data.lane_tops.findIndex((top) => y >= row.y + top && y < row.y + top + data.lane_height);
+    return num >= 0 && !hidden[num] ? num : null;
+  }
+
+  function levelAt(lane, time) {
+    let level = lane.transitions.length ? lane.transitions[0][1] : "L";
+    for (const [start, value] of lane.transitions) {
+      if (start > time) break;
+      level = value;
+    }
+    return level;
+  }
+
+  function format(time) {
+    if (!data.timebase) return time.toFixed(2) + " T";
+    const [factor, unit] = data.unit;
+    return (time * data.timebase / factor).toFixed(2) + " " + unit;
+  }
+
+  function drawCursors() {
+    overlay.replaceChildren();
+    cursors.forEach((time, num) => {
+      // wrapped diagrams show the cursor in every row holding its time.
+      for (const row of data.rows.filter((row) => time >= row.start && time <= row.end)) {
+        const x = row.x + time * data.period_width;
+        const line = document.createElementNS(NS, "line");
+        line.setAttribute("x1", x);
+        line.setAttribute("x2", x);
+        line.setAttribute("y1", row.y);
+        line.setAttribute("y2", row.y + lanesHeight);
+        line.setAttribute("stroke", num === 0 ? "#0D84F0" : "#FF5733");
+        line.setAttribute("stroke-width", 1.5);
+        overlay.appendChild(line);
+      }
+    });
+    if (cursors.length === 0) {
+      cursorInfo.textContent = "Click into the diagram to set measurement cursors.";
+    } else if (cursors.length === 1) {
+      cursorInfo.textContent = "A: " + format(cursors[0]);
+    } else {
+      cursorInfo.textContent = "A: " + format(cursors[0]) + "   B: " + format(cursors[1]) +
+        "   Δt: " + format(Math.abs(cursors[1] - cursors[0]));
+    }
+  }
+
+  svg.addEventListener("mousemove", (evt) => {
+    const pt = toSvg(evt);
+    const hit = rowAt(pt);
+    const num = hit && laneAt(hit.row, pt.y);
+    if (num === null) {
+      tooltip.style.display = "none";
+      return;
+    }
+    const time = hit.time;
+    const lane = data.lanes[num];
+    tooltip.textContent = lane.name + ": " + levelAt(lane, time) + " @ " + format(time);
+    const rect = container.getBoundingClientRect();
+    tooltip.style.left = (evt.clientX - rect.left + 12) + "px";
+    tooltip.style.top = (evt.clientY - rect.top + 12) + "px";
+    tooltip.style.display = "block";
+  });
+
+  svg.addEventListener("mouseleave", () => { tooltip.style.display = "none"; });
+
+  svg.addEventListener("click", (evt) => {
+    const hit = rowAt(toSvg(evt));
+    if (!hit) return;
+    const time = hit.time;
+    cursors = cursors.length >= 2 ? [time] : cursors.concat([time]);
+    drawCursors();
+  });
+
+  svg.addEventListener("contextmenu", (evt) => {
+    evt.preventDefault();
+    cursors = [];
+    drawCursors();
+  });
+
+  const lanes = document.getElementById("siggi-lanes");
+  data.lanes.forEach((lane, num) => {
+    const label = document.createElement("label");
+    const box = document.createElement("input");
+    box.type = "checkbox";
+    box.checked = true;
+    box.addEventListener("change", () => {
+      hidden[num] = !box.checked;
+      for (const row of data.rows) {
+        const group = svg.getElementById(row.id_prefix + "lane-" + num);
+        if (group) group.style.display = box.checked ? "" : "none";
+      }
+    });
+    label.appendChild(box);
+    label.appendChild(document.createTextNode(" " + lane.name));
+    lanes.appendChild(label);
+  });
+})();
+</script>
+</body>
+</html>
+"##;
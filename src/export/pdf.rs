@@ -0,0 +1,63 @@
+// export/pdf.rs
+use std::{collections::HashMap, fs, path::Path};
+
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref};
+use svg2pdf::ConversionOptions;
+
+use super::{error::ExportError, to_usvg_tree};
+
+// svg user units are css pixels (96 per inch), pdf units are points (72 per inch).
+const PT_PER_PX: f32 = 72.0 / 96.0;
+
+/// Convert composed documents into a pdf file with one page per document.
+/// Text is kept as text and the used fonts get embedded.
+pub fn to_pdf(docs: &[svg::Document]) -> Result<Vec<u8>, ExportError> {
+    let mut alloc = Ref::new(1);
+    let catalog_id = alloc.bump();
+    let page_tree_id = alloc.bump();
+
+    let mut pdf = Pdf::new();
+    let mut page_ids = Vec::<Ref>::new();
+    let svg_name = Name(b"S1");
+
+    for doc in docs {
+        let tree = to_usvg_tree(doc)?;
+        let (chunk, svg_id) = svg2pdf::to_chunk(&tree, ConversionOptions::default())?;
+
+        // move the chunk's references behind the ones already in use.
+        let mut map = HashMap::new();
+        let chunk = chunk.renumber(|old| *map.entry(old).or_insert_with(|| alloc.bump()));
+        let svg_id = map[&svg_id];
+
+        let page_id = alloc.bump();
+        let content_id = alloc.bump();
+        let width = tree.size().width() * PT_PER_PX;
+        let height = tree.size().height() * PT_PER_PX;
+
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, width, height));
+        page.parent(page_tree_id);
+        page.contents(content_id);
+        page.resources().x_objects().pair(svg_name, svg_id);
+        page.finish();
+
+        // the svg xobject has a size of 1x1, scale it to the page.
+        let mut content = Content::new();
+        content.transform([width, 0.0, 0.0, height, 0.0, 0.0]).x_object(svg_name);
+        pdf.stream(content_id, &content.finish());
+        pdf.extend(&chunk);
+
+        page_ids.push(page_id);
+    }
+
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id).count(page_ids.len() as i32).kids(page_ids);
+
+    Ok(pdf.finish())
+}
+
+/// Convert composed documents and save them as pages of a pdf file.
+pub fn save<P: AsRef<Path>>(path: P, docs: &[svg::Document]) -> Result<(), ExportError> {
+    fs::write(path, to_pdf(docs)?)?;
+    Ok(())
+}
@@ -0,0 +1,49 @@
+// export/png.rs
+use std::{fs, path::Path};
+
+use resvg::tiny_skia::{Pixmap, Transform};
+
+use super::{error::ExportError, to_usvg_tree};
+
+// svg user units are defined as css pixels, which are 96 per inch.
+const SVG_DPI: f64 = 96.0;
+
+/// Defines the size of the raster image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PngSize {
+    /// Scale the diagram to the given resolution in dots per inch.
+    Dpi(f64),
+    /// Scale the diagram to the given width in pixels and keep the aspect ratio.
+    Width(u32),
+}
+
+impl Default for PngSize {
+    fn default() -> Self {
+        PngSize::Dpi(SVG_DPI)
+    }
+}
+
+/// Rasterize a composed document and return the encoded png data.
+pub fn to_png(doc: &svg::Document, size: PngSize) -> Result<Vec<u8>, ExportError> {
+    let tree = to_usvg_tree(doc)?;
+    let svg_size = tree.size();
+
+    let scale = match size {
+        PngSize::Dpi(dpi) => dpi / SVG_DPI,
+        PngSize::Width(width) => width as f64 / svg_size.width() as f64,
+    } as f32;
+
+    let width = (svg_size.width() * scale).ceil() as u32;
+    let height = (svg_size.height() * scale).ceil() as u32;
+    let mut pixmap = Pixmap::new(width, height).ok_or(ExportError::RenderError)?;
+
+    resvg::render(&tree, Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+
+    pixmap.encode_png().map_err(|err| ExportError::EncodeError(err.to_string()))
+}
+
+/// Rasterize a composed document and save it as png file.
+pub fn save<P: AsRef<Path>>(path: P, doc: &svg::Document, size: PngSize) -> Result<(), ExportError> {
+    fs::write(path, to_png(doc, size)?)?;
+    Ok(())
+}
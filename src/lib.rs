@@ -1,3 +1,5 @@
 pub mod model;
 pub mod parse;
-pub mod compose;
\ No newline at end of file
+pub mod compose;
+pub mod markdown;
+pub mod export;
@@ -1,9 +1,9 @@
 
-use std::{fs};
+use std::{fs, io::{self, Read}, path::Path, process};
 
-use siggi::{compose::Compositor, parse};
+use siggi::{compose::{Backend, Compositor, Animation, TextMode, text::TextCompositor, tikz::TikzCompositor, vcd::VcdCompositor}, model::{Style, TimeAxis}, parse, markdown::{self, Embed}, export::{png::{self, PngSize}, pdf, html::HtmlCompositor, error::ExportError}};
 
-use clap::Parser as ClapParser;
+use clap::{ArgEnum, Parser as ClapParser, Subcommand};
 
 #[derive(ClapParser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -18,10 +18,20 @@ use clap::Parser as ClapParser;
     #[clap(default_value_t = String::from("Siggi Diagram"))]
     title: String,
 
+    /// Output file, the format is selected by the extension (svg, png, pdf, html, txt, tex or vcd)
     #[clap(short, long)]
     #[clap(default_value_t = String::from("./outputs/siggi.svg"))]
     output: String,
 
+    /// Resolution of png outputs
+    #[clap(long, conflicts_with = "width")]
+    dpi: Option<f64>,
+
+    /// Width in pixels of png outputs
+    #[clap(long)]
+    width: Option<u32>,
+
+    /// Use the dark theme
     #[clap(short,long)]
     dark: bool,
 
@@ -31,43 +41,303 @@ use clap::Parser as ClapParser;
     #[clap(long)]
     tocks: bool,
 
+    /// Save every lane as its own diagram next to the output
     #[clap(long)]
     split: bool,
 
+    /// Keep the title on the split lanes
+    #[clap(long, requires = "split")]
+    split_title: bool,
+
+    /// Directory for the split lanes instead of the output's directory
+    #[clap(long, requires = "split")]
+    split_dir: Option<String>,
+
+    /// Animate the svg output, revealing the waves within the given seconds
+    #[clap(long)]
+    animate: Option<f64>,
+
+    /// Play the animation only once
+    #[clap(long, requires = "animate")]
+    once: bool,
+
+    /// Show a time cursor moving along with the animation
+    #[clap(long, requires = "animate")]
+    cursor: bool,
+
+    /// How texts are written: with the installed fonts, with an embedded font or as outline paths
+    #[clap(long, arg_enum, default_value = "font")]
+    text: TextArg,
+
+    /// Duration of one period, e.g. 10ns, used to label the time axis
+    #[clap(long, parse(try_from_str = parse::parse_duration))]
+    timebase: Option<f64>,
+
+    /// Draw a time axis with a tick per period below the lanes
+    #[clap(long)]
+    time_axis: bool,
+
+    /// Break the diagram into rows of the given number of periods
+    #[clap(long)]
+    wrap: Option<u32>,
+
+    /// Only draw the diagram from this time on (in periods)
+    #[clap(long)]
+    from: Option<f64>,
+
+    /// Only draw the diagram until this time (in periods)
+    #[clap(long)]
+    to: Option<f64>,
+
+    /// Show "…" where the diagram is cut off by --from or --to
+    #[clap(long)]
+    cut_marks: bool,
+
+    /// Predefined style used for the diagram: default, compact, print or presentation
+    #[clap(long)]
+    style: Option<Style>,
+
+    /// Print a text preview of the diagram
+    #[clap(short, long)]
+    preview: bool,
+
     #[clap(short,long)]
-    input_file: Option<String>
+    input_file: Option<String>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
 }
 
-fn main() {
-    println!("Starting, siggi!");
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum TextArg {
+    Font,
+    Embed,
+    Paths,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Replace ```siggi code blocks in a markdown file with the rendered diagrams
+    Markdown {
+        input: String,
+
+        /// Write the result to this file instead of stdout
+        #[clap(short, long)]
+        output: Option<String>,
 
+        /// Save the diagrams as svg files into this directory (relative to the output) and link them
+        #[clap(long)]
+        link_dir: Option<String>,
+    },
+    /// Print the json schema of the input file format
+    Schema {
+        /// Write the schema to this file instead of stdout
+        #[clap(short, long)]
+        output: Option<String>,
+    },
+    /// Run as mdBook preprocessor
+    Mdbook {
+        #[clap(subcommand)]
+        command: Option<MdbookCommand>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum MdbookCommand {
+    /// Check if the renderer is supported
+    Supports { renderer: String },
+}
+
+fn main() {
     let args = Args::parse();
-    let comp = Compositor::default();
+    let mut comp = Compositor::default();
+    if let Some(style) = args.style.clone() {
+        comp.style(style);
+    }
+    if let Some(periods) = args.wrap {
+        comp.wrap(periods);
+    }
+    if args.from.is_some() || args.to.is_some() {
+        comp.zoom(args.from.unwrap_or(0.0), args.to.unwrap_or(f64::INFINITY));
+    }
+    comp.cut_marks(args.cut_marks);
+    comp.split_title(args.split_title);
+    comp.text_mode(match args.text {
+        TextArg::Font => TextMode::Font,
+        TextArg::Embed => TextMode::Embedded,
+        TextArg::Paths => TextMode::Paths,
+    });
+    // only svg and html outputs can be animated.
+    let animated_output = has_extension(&args.output, "svg") || has_extension(&args.output, "html");
+    if let (Some(duration), true) = (args.animate, animated_output) {
+        comp.animate(Animation { duration, repeat: !args.once, cursor: args.cursor });
+    }
+    let png_size = match (args.dpi, args.width) {
+        (_, Some(width)) => PngSize::Width(width),
+        (Some(dpi), _) => PngSize::Dpi(dpi),
+        _ => PngSize::default(),
+    };
+
+    if let Some(command) = args.command {
+        run_command(command, &comp);
+        return;
+    }
+
+    println!("Starting, siggi!");
 
-    if let Some(path) = args.input_file {
+    let mut diagram = if let Some(path) = &args.input_file {
         println!("Reading input file ... ");
         let data = fs::read_to_string(path).expect("Unable to read file");
         println!("Parsing content ... ");
         let diagram = parse::from_json_str(&data).expect("Error while parsing signals");
-        println!("Compose svg output ... ");
-        let doc = comp.compose(&diagram);
-        svg::save(&args.output, &doc).expect("Could not save the diagram.");
-        println!("SVG saved to {}", &args.output);
+        diagram.dark(args.dark)
+    } else {
+        println!("Parsing specified args ... ");
+        parse::from_args(args.title, args.dark, args.clock, args.signal).expect("Parsing error")
+    };
+
+    if let Some(timebase) = args.timebase {
+        diagram.set_timebase(Some(timebase));
+    }
+    if args.time_axis && diagram.time_axis().is_none() {
+        diagram.set_time_axis(Some(TimeAxis::default()));
+    }
+
+    if args.preview {
+        println!("{}", TextCompositor::default().compose(&diagram));
+    }
+
+    // these formats are composed directly from the diagram.
+    if let Some(backend) = text_backend(&args.output, &comp) {
+        let content = backend.render(&diagram);
+        fs::write(&args.output, content).expect("Could not save the diagram.");
+        println!("Diagram saved to {}", &args.output);
         if args.split {
-            let docs = comp.compose_splits(&diagram);
-            for (idx,doc) in docs.iter().enumerate() {
-                let output_path = format!("{}-lane-{}.svg",args.output.split('.').collect::<Vec<&str>>()[0], idx+1);
-                svg::save(&output_path, doc).expect("Could not save the diagram.");
-                println!("Lane {} saved to {}", idx, &output_path);
+            for (name, lane) in diagram.split_lanes(args.split_title) {
+                let output_path = split_path(&args.output, args.split_dir.as_deref(), &name);
+                fs::write(&output_path, backend.render(&lane)).expect("Could not save the diagram.");
+                println!("Lane {} saved to {}", name, &output_path);
             }
         }
+        return;
+    }
+
+    println!("Compose svg output ... ");
+    let doc = comp.compose(&diagram);
+    if args.split && has_extension(&args.output, "pdf") {
+        // all lanes go as separate pages into the same pdf.
+        let mut docs = vec![doc];
+        docs.extend(comp.compose_splits(&diagram).into_iter().map(|(_, doc)| doc));
+        exit_on_error(pdf::save(&args.output, &docs));
+        println!("Diagram and {} lanes saved to {}", docs.len() - 1, &args.output);
+        return;
+    }
+    save(&args.output, &doc, png_size);
+    println!("Diagram saved to {}", &args.output);
+    if args.split {
+        for (name, doc) in comp.compose_splits(&diagram) {
+            let output_path = split_path(&args.output, args.split_dir.as_deref(), &name);
+            save(&output_path, &doc, png_size);
+            println!("Lane {} saved to {}", name, &output_path);
+        }
+    }
+}
+
+// Path of a split lane, either in the split directory or next to the output.
+fn split_path(output: &str, split_dir: Option<&str>, name: &str) -> String {
+    let output = Path::new(output);
+    let path = match split_dir {
+        Some(dir) => {
+            fs::create_dir_all(dir).expect("Could not create the split directory.");
+            Path::new(dir).join(name)
+        }
+        None => {
+            let stem = output.file_stem().and_then(|s| s.to_str()).unwrap_or("siggi");
+            output.with_file_name(format!("{}-{}", stem, name))
+        }
+    };
+    path.with_extension(output.extension().unwrap_or_default()).to_string_lossy().into_owned()
+}
+
+// Save the document in the format given by the file extension.
+fn save(path: &str, doc: &svg::Document, png_size: PngSize) {
+    let result = if has_extension(path, "png") {
+        png::save(path, doc, png_size)
+    } else if has_extension(path, "pdf") {
+        pdf::save(path, std::slice::from_ref(doc))
     } else {
-        println!("Parsing specified args ... ");
-        let diagram = parse::from_args(args.title, args.dark, args.clock, args.signal).expect("Parsing error");
-        println!("Compose svg output ... ");
-        let doc = comp.compose(&diagram);
-        svg::save(&args.output, &doc).expect("Could not save the diagram.");
-        println!("SVG saved to {}", &args.output)
+        svg::save(path, doc).map_err(ExportError::from)
+    };
+    exit_on_error(result);
+}
+
+// Report a failed export as error message and stop.
+fn exit_on_error(result: Result<(), ExportError>) {
+    if let Err(err) = result {
+        eprintln!("Could not save the diagram.{}", err);
+        process::exit(1);
+    }
+}
+
+// Backend writing the format given by the file extension as text, if the format isn't composed from the svg.
+fn text_backend<'a>(path: &str, comp: &'a Compositor) -> Option<Box<dyn Backend<Output = String> + 'a>> {
+    let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "txt" => Some(Box::new(TextCompositor::default())),
+        "tex" => Some(Box::new(TikzCompositor::default().standalone(true))),
+        "vcd" => Some(Box::new(VcdCompositor::default())),
+        "html" => Some(Box::new(HtmlCompositor::new(comp))),
+        _ => None,
     }
 }
 
+fn has_extension(path: &str, extension: &str) -> bool {
+    Path::new(path).extension().is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
+fn run_command(command: Command, comp: &Compositor) {
+    match command {
+        Command::Markdown { input, output, link_dir } => {
+            let data = fs::read_to_string(&input).expect("Unable to read file");
+            let embed = match link_dir {
+                Some(dir) => {
+                    let name = Path::new(&input).file_stem().and_then(|s| s.to_str()).unwrap_or("siggi").to_string();
+                    Embed::Link { dir, name }
+                }
+                None => Embed::Inline,
+            };
+            let rendered = markdown::render(&data, comp, &embed).expect("Error while rendering markdown");
+
+            if let Embed::Link { dir, .. } = &embed {
+                let base = output.as_ref().and_then(|o| Path::new(o).parent()).unwrap_or_else(|| Path::new(""));
+                let dir = base.join(dir);
+                fs::create_dir_all(&dir).expect("Could not create the output directory.");
+                for (name, doc) in rendered.files.iter() {
+                    svg::save(dir.join(name), doc).expect("Could not save the diagram.");
+                }
+            }
+
+            match output {
+                Some(output) => fs::write(output, rendered.markdown).expect("Could not save the markdown file."),
+                None => print!("{}", rendered.markdown),
+            }
+        }
+        Command::Schema { output } => {
+            let schema = parse::json_schema();
+            match output {
+                Some(output) => fs::write(output, schema).expect("Could not save the schema."),
+                None => println!("{}", schema),
+            }
+        }
+        Command::Mdbook { command: Some(MdbookCommand::Supports { renderer }) } => {
+            // Inline svgs are only useful for html output.
+            process::exit(if renderer == "html" { 0 } else { 1 });
+        }
+        Command::Mdbook { command: None } => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).expect("Unable to read stdin");
+            let book = markdown::preprocess_book(&input, comp).expect("Error while preprocessing book");
+            print!("{}", book);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+// markdown.rs
+//
+// Replace ```siggi fenced code blocks in markdown documents by rendered diagrams.
+// Can also act as an mdBook preprocessor, see `preprocess_book`.
+use serde_json::Value;
+
+use crate::{compose::Compositor, parse::{self, error::ParseError}};
+
+const FENCE_LANG: &str = "siggi";
+
+/// Describes how a rendered diagram replaces its code block.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Embed {
+    /// Insert the svg inline into the document.
+    #[default]
+    Inline,
+    /// Insert an image link pointing to `<dir>/<name>`.
+    /// The documents are returned in `Rendered::files` and need to be saved by the caller.
+    Link { dir: String, name: String },
+}
+
+/// The result of rendering a markdown document.
+#[derive(Debug, Default)]
+pub struct Rendered {
+    pub markdown: String,
+    /// Generated files as (file name, document) pairs. Only used with `Embed::Link`.
+    pub files: Vec<(String, svg::Document)>,
+}
+
+/// Render all ```siggi blocks inside the given markdown text.
+pub fn render(markdown: &str, comp: &Compositor, embed: &Embed) -> Result<Rendered, ParseError> {
+    let mut rendered = Rendered::default();
+    let mut output = Vec::<String>::new();
+    let mut lines = markdown.lines();
+
+    while let Some(line) = lines.next() {
+        let (fence, info) = match open_fence(line) {
+            Some(open) => open,
+            None => {
+                output.push(line.to_string());
+                continue;
+            }
+        };
+
+        // Other code blocks are kept as they are, even if they contain a ```siggi example.
+        if info != FENCE_LANG {
+            output.push(line.to_string());
+            for line in lines.by_ref() {
+                output.push(line.to_string());
+                if fence.closed_by(line) {
+                    break;
+                }
+            }
+            continue;
+        }
+
+        let mut block = Vec::<&str>::new();
+        for line in lines.by_ref() {
+            if fence.closed_by(line) {
+                break;
+            }
+            block.push(line);
+        }
+
+        let diagram = parse::from_json_str(&block.join("\n"))?;
+        let doc = comp.compose(&diagram);
+
+        match embed {
+            Embed::Inline => output.push(doc.to_string()),
+            Embed::Link { dir, name } => {
+                let file_name = format!("{}-{}.svg", name, rendered.files.len() + 1);
+                let path = if dir.is_empty() { file_name.clone() } else { format!("{}/{}", dir.trim_end_matches('/'), file_name) };
+                output.push(format!("![{}]({})", diagram.title(), path));
+                rendered.files.push((file_name, doc));
+            }
+        }
+    }
+
+    rendered.markdown = output.join("\n");
+    if markdown.ends_with('\n') {
+        rendered.markdown.push('\n');
+    }
+    Ok(rendered)
+}
+
+/// Run as mdBook preprocessor.
+/// Takes the `[context, book]` json mdBook writes to stdin and returns the processed book json.
+pub fn preprocess_book(input: &str, comp: &Compositor) -> Result<String, ParseError> {
+    let input: Value = serde_json::from_str(input)?;
+    let mut book = match input {
+        Value::Array(mut arr) if arr.len() == 2 => arr.remove(1),
+        _ => return Err(ParseError::ParseBookError),
+    };
+
+    // mdBook 0.4 calls the list `sections`, newer versions `items`.
+    for key in ["sections", "items"] {
+        if let Some(Value::Array(items)) = book.get_mut(key) {
+            preprocess_book_items(items, comp)?;
+        }
+    }
+
+    Ok(serde_json::to_string(&book)?)
+}
+
+fn preprocess_book_items(items: &mut [Value], comp: &Compositor) -> Result<(), ParseError> {
+    for item in items.iter_mut() {
+        if let Some(chapter) = item.get_mut("Chapter") {
+            if let Some(Value::String(content)) = chapter.get_mut("content") {
+                *content = render(content, comp, &Embed::Inline)?.markdown;
+            }
+            if let Some(Value::Array(sub_items)) = chapter.get_mut("sub_items") {
+                preprocess_book_items(sub_items, comp)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Opening fence of a code block.
+struct Fence {
+    fence_char: char,
+    len: usize,
+}
+
+impl Fence {
+    // A block is closed by a fence of the same character which is at least as long as the opening one.
+    fn closed_by(&self, line: &str) -> bool {
+        let line = line.trim();
+        line.chars().all(|c| c == self.fence_char) && line.chars().count() >= self.len
+    }
+}
+
+// Returns the fence and the info string if the line opens a code block.
+fn open_fence(line: &str) -> Option<(Fence, &str)> {
+    let line = line.trim();
+    for fence_char in ['`', '~'] {
+        let len = line.chars().take_while(|c| *c == fence_char).count();
+        if len >= 3 {
+            return Some((Fence { fence_char, len }, line[len..].trim()));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIAGRAM: &str = r#"{"signals":[{"name":"A","wave":"lh"}]}"#;
+
+    #[test]
+    fn renders_siggi_blocks() {
+        let markdown = format!("# Title\n```siggi\n{}\n```\ntext\n", DIAGRAM);
+        let rendered = render(&markdown, &Compositor::default(), &Embed::Inline).unwrap();
+        assert!(rendered.markdown.starts_with("# Title\n<svg"));
+        assert!(rendered.markdown.ends_with("</svg>\ntext\n"));
+    }
+
+    #[test]
+    fn keeps_siggi_examples_inside_other_blocks() {
+        for fence in ["````", "~~~"] {
+            let markdown = format!("{}markdown\n```siggi\n{}\n```\n{}\n", fence, DIAGRAM, fence);
+            let rendered = render(&markdown, &Compositor::default(), &Embed::Inline).unwrap();
+            assert_eq!(rendered.markdown, markdown);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+// model/axis.rs
+//
+// Time ruler below the lanes and the physical units of its labels.
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+
+// Units used to format times, from large to small.
+const UNITS: [(f64, &str); 6] = [(1.0, "s"), (1e-3, "ms"), (1e-6, "µs"), (1e-9, "ns"), (1e-12, "ps"), (1e-15, "fs")];
+
+/// Tick marks with time labels drawn below the lanes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct TimeAxis {
+    /// Number of periods between two major ticks.
+    pub step: f64,
+    /// Number of minor ticks between two major ticks.
+    pub minor: u32,
+    /// Show the time at each major tick.
+    pub labels: bool,
+}
+
+impl Default for TimeAxis {
+    fn default() -> Self {
+        Self { step: 1.0, minor: 0, labels: true }
+    }
+}
+
+impl TimeAxis {
+    pub fn new() -> Self { Self::default() }
+
+    /// Set the number of periods between two major ticks.
+    /// Supports builder pattern
+    pub fn step(mut self, step: f64) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Set the number of minor ticks between two major ticks.
+    /// Supports builder pattern
+    pub fn minor(mut self, minor: u32) -> Self {
+        self.minor = minor;
+        self
+    }
+
+    /// Show or hide the time labels.
+    /// Supports builder pattern
+    pub fn labels(mut self, labels: bool) -> Self {
+        self.labels = labels;
+        self
+    }
+}
+
+/// Get the largest unit (factor and symbol) in which the duration in seconds is at least 1.
+pub fn time_unit(seconds: f64) -> (f64, &'static str) {
+    UNITS.iter().copied()
+        .find(|(factor, _)| seconds.abs() >= factor * (1.0 - 1e-9))
+        .unwrap_or(UNITS[UNITS.len() - 1])
+}
+
+/// Format a duration in seconds with the given unit, e.g. `2.5 ns`.
+pub fn format_time(seconds: f64, unit: (f64, &str)) -> String {
+    format!("{} {}", format_number(seconds / unit.0), unit.1)
+}
+
+/// Format a number with up to three decimals and without trailing zeros.
+pub fn format_number(value: f64) -> String {
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_string() } else { text.to_string() }
+}
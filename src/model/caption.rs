@@ -0,0 +1,46 @@
+// model/caption.rs
+//
+// Text block below a diagram, e.g. a numbered figure caption.
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+
+use super::marker::TextAnchor;
+
+/// Caption below the diagram, which may span several lines.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Caption {
+    /// Text of the caption, lines are separated by `\n`.
+    pub text: String,
+    /// Alignment of the lines: left, center or right.
+    pub align: TextAnchor,
+    /// Figure number written in front of the text.
+    pub number: Option<u32>,
+}
+
+impl Caption {
+    pub fn new<T: Into<String>>(text: T) -> Self { Self { text: text.into(), ..Default::default() } }
+
+    /// Set the alignment of the lines.
+    /// Supports builder pattern
+    pub fn align(mut self, align: TextAnchor) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Set the figure number.
+    /// Supports builder pattern
+    pub fn number(mut self, number: u32) -> Self {
+        self.number = Some(number);
+        self
+    }
+
+    /// Get the caption's lines, the first one starts with the figure number.
+    pub fn lines(&self) -> Vec<String> {
+        let text = match self.number {
+            Some(number) => format!("Figure {}: {}", number, self.text),
+            None => self.text.clone(),
+        };
+        text.lines().map(String::from).collect()
+    }
+}
@@ -1,18 +1,41 @@
 
-use super::{utils::Color, marker::{Marker, Label, TextSize}, Signal};
+use serde::{Serialize, Deserialize};
 
+use super::{utils::Color, marker::{Marker, Label, TextSize}, Signal, Style, Theme, TimeAxis, Edge, Point, Dimension, Region, Caption, Legend, legend::{LegendEntry, Swatch}};
 
-#[derive(Debug, Clone)]
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Diagram {
     title: Option<String>,
+    subtitle: Option<String>,
+    caption: Option<Caption>,
+    /// Source or author line below the caption.
+    source: Option<String>,
     lanes: Vec<Lane>,
     background: Color,
     xaxis: Option<String>,
+    style: Option<Style>,
+    theme: Theme,
+    groups: Vec<LaneGroup>,
+    edges: Vec<Edge>,
+    legend: Option<Legend>,
+    /// Regions shaded across all lanes.
+    regions: Vec<Region>,
+    time_axis: Option<TimeAxis>,
+    /// Duration of one period in seconds.
+    timebase: Option<f64>,
+    // groups started with `start_group` which aren't ended yet.
+    #[serde(skip)]
+    open_groups: Vec<usize>,
 }
 
 impl Default for Diagram {
     fn default() -> Self {
-        Self { title: Default::default(), lanes: Default::default(), background: Color::White, xaxis: None }
+        Self {
+            title: Default::default(), subtitle: None, caption: None, source: None, lanes: Default::default(), background: Color::White, xaxis: None, style: None, theme: Theme::default(),
+            groups: Default::default(), edges: Default::default(), legend: None, regions: Default::default(), time_axis: None, timebase: None, open_groups: Default::default(),
+        }
     }
 }
 
@@ -29,6 +52,34 @@ impl Diagram {
         Self { title, background: Color::White, ..Default::default() } 
     }
 
+    /// Adds a subtitle below the title
+    /// Supports builder pattern
+    pub fn with_subtitle<T: Into<String>>(mut self, subtitle: T) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// Adds a caption below the diagram
+    /// Supports builder pattern
+    pub fn with_caption(mut self, caption: Caption) -> Self {
+        self.caption = Some(caption);
+        self
+    }
+
+    /// Adds a source or author line below the diagram
+    /// Supports builder pattern
+    pub fn with_source<T: Into<String>>(mut self, source: T) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Adds a legend to the diagram
+    /// Supports builder pattern
+    pub fn with_legend(mut self, legend: Legend) -> Self {
+        self.legend = Some(legend);
+        self
+    }
+
     /// Adds an xaxis arrow to the bottom of the diagram
     /// Supports builder pattern
     pub fn has_xaxis(mut self, xaxis: &str) -> Self {
@@ -36,17 +87,46 @@ impl Diagram {
         self
     }
 
-    /// Set the diagram's background to Darkgray if true.
+    /// Adds a time ruler to the bottom of the diagram
     /// Supports builder pattern
-    pub fn dark(mut self, dark: bool) -> Self {
+    pub fn with_time_axis(mut self, time_axis: TimeAxis) -> Self {
+        self.time_axis = Some(time_axis);
+        self
+    }
+
+    /// Set the duration of one period in seconds, which is used to label the time axis.
+    /// Supports builder pattern
+    pub fn with_timebase(mut self, seconds: f64) -> Self {
+        self.timebase = Some(seconds);
+        self
+    }
+
+    /// Use the dark theme if true.
+    /// Supports builder pattern
+    pub fn dark(self, dark: bool) -> Self {
         if dark {
-            self.background = Color::Darkgray;
+            return self.with_theme(Theme::dark());
         }
         self
     }
 
+    /// Set the theme and its background.
+    /// Supports builder pattern
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.set_theme(theme);
+        self
+    }
+
+    /// Set the style used to compose the diagram.
+    /// Supports builder pattern
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
     /// Add a lane to the diagram.
     /// Supports builder pattern
+    #[allow(clippy::should_implement_trait)]
     pub fn add(mut self, lane: Lane) -> Self {
         self.lanes.push(lane);
         self
@@ -58,26 +138,164 @@ impl Diagram {
         self
     }
 
+    /// Add a named group of lanes to the diagram.
+    /// Supports builder pattern
+    pub fn add_group<T: Into<String>>(mut self, name: T, lanes: Vec<Lane>) -> Self {
+        self.start_group(name);
+        for lane in lanes {
+            self.append(lane);
+        }
+        self.end_group();
+        self
+    }
+
+    /// Add an edge between two named points of the lanes.
+    /// Supports builder pattern
+    pub fn add_edge(mut self, edge: Edge) -> Self {
+        self.edges.push(edge);
+        self
+    }
+
+    /// Append an edge between two named points of the lanes.
+    pub fn append_edge(&mut self, edge: Edge) -> &mut Self {
+        self.edges.push(edge);
+        self
+    }
+
+    /// Add a region shaded across all lanes.
+    /// Supports builder pattern
+    pub fn add_region(mut self, region: Region) -> Self {
+        self.regions.push(region);
+        self
+    }
+
+    /// Append a region shaded across all lanes.
+    pub fn append_region(&mut self, region: Region) -> &mut Self {
+        self.regions.push(region);
+        self
+    }
+
+    /// Start a named group, which contains all lanes and groups appended until `end_group` is called.
+    /// Groups can be nested.
+    pub fn start_group<T: Into<String>>(&mut self, name: T) -> &mut Self {
+        self.open_groups.push(self.groups.len());
+        self.groups.push(LaneGroup { name: name.into(), start: self.lanes.len(), len: 0 });
+        self
+    }
+
+    /// End the group started last.
+    pub fn end_group(&mut self) -> &mut Self {
+        if let Some(idx) = self.open_groups.pop() {
+            let group = &mut self.groups[idx];
+            group.len = self.lanes.len() - group.start;
+        }
+        self
+    }
+
     /// Set the diagram's title.
     pub fn set_title(&mut self, title: Option<String>) {
         self.title = title;
     }
 
+    /// Set the diagram's subtitle.
+    pub fn set_subtitle(&mut self, subtitle: Option<String>) {
+        self.subtitle = subtitle;
+    }
+
+    /// Set the diagram's caption.
+    pub fn set_caption(&mut self, caption: Option<Caption>) {
+        self.caption = caption;
+    }
+
+    /// Set the diagram's source line.
+    pub fn set_source(&mut self, source: Option<String>) {
+        self.source = source;
+    }
+
+    /// Set the diagram's legend.
+    pub fn set_legend(&mut self, legend: Option<Legend>) {
+        self.legend = legend;
+    }
+
     /// Set the diagram's background.
     pub fn set_background(&mut self, background: Color) {
         self.background = background;
     }
 
+    /// Set the diagram's theme together with the theme's background.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.background = theme.background;
+    }
+
+    /// Set the diagram's time axis.
+    pub fn set_time_axis(&mut self, time_axis: Option<TimeAxis>) {
+        self.time_axis = time_axis;
+    }
+
+    /// Set the duration of one period in seconds.
+    pub fn set_timebase(&mut self, seconds: Option<f64>) {
+        self.timebase = seconds;
+    }
+
     /// Set the diagram's xaxis.
     pub fn set_xaxis(&mut self, xaxis: &str) {
         self.xaxis = Some(String::from(xaxis));
     }
 
+    /// Set the diagram's style.
+    pub fn set_style(&mut self, style: Option<Style>) {
+        self.style = style;
+    }
+
     /// Get a reference to the diagram's title.
     pub fn title(&self) -> String {
         self.title.as_ref().unwrap_or(&String::default()).clone()  // todo!() change clone() to return &String
     }
 
+    /// Get a reference to the diagram's subtitle.
+    pub fn subtitle(&self) -> Option<&String> {
+        self.subtitle.as_ref()
+    }
+
+    /// Get a reference to the diagram's caption.
+    pub fn caption(&self) -> Option<&Caption> {
+        self.caption.as_ref()
+    }
+
+    /// Get a reference to the diagram's source line.
+    pub fn source(&self) -> Option<&String> {
+        self.source.as_ref()
+    }
+
+    /// Get a reference to the diagram's legend.
+    pub fn legend(&self) -> Option<&Legend> {
+        self.legend.as_ref()
+    }
+
+    /// Get the entries of the legend, followed by the collected signals and markers with a description.
+    /// Entries are only listed once.
+    pub fn legend_entries(&self) -> Vec<LegendEntry> {
+        let legend = match &self.legend {
+            Some(legend) => legend,
+            None => return Vec::new(),
+        };
+        let mut entries = legend.entries.clone();
+        if legend.auto {
+            for lane in self.lanes.iter() {
+                let signal = &lane.signal;
+                let collected = std::iter::once(LegendEntry::new(signal.color, Swatch::Line, signal.description.as_str()))
+                    .chain(lane.markers.iter().map(|m| LegendEntry::new(m.color, if m.dashed { Swatch::Dashed } else { Swatch::Line }, m.description.as_str())));
+                for entry in collected {
+                    if !entry.description.is_empty() && !entries.contains(&entry) {
+                        entries.push(entry);
+                    }
+                }
+            }
+        }
+        entries
+    }
+
     /// Get the diagram's background.
     pub fn background(&self) -> Color {
         self.background
@@ -93,18 +311,143 @@ impl Diagram {
         self.lanes.as_ref()
     }
 
+    /// Get a diagram which only contains the lane with the given index.
+    /// The settings of the diagram are kept, except for the lane groups, the caption and the source line.
+    /// Edges are only kept if both of their points lie on the lane.
+    pub fn split_lane(&self, idx: usize) -> Option<Diagram> {
+        let lane = self.lanes.get(idx)?;
+        let mut diagram = Diagram {
+            lanes: vec![lane.clone()],
+            groups: Vec::new(),
+            open_groups: Vec::new(),
+            edges: Vec::new(),
+            caption: None,
+            source: None,
+            ..self.clone()
+        };
+        diagram.edges = self.edges.iter()
+            .filter(|edge| diagram.find_point(&edge.from).is_some() && diagram.find_point(&edge.to).is_some())
+            .cloned()
+            .collect();
+        Some(diagram)
+    }
+
+    /// Split the diagram into one diagram per lane, see `split_lane`.
+    /// Each one is named after its signal, the names are unique and usable as file names.
+    /// The title and subtitle are removed unless `keep_title` is set.
+    pub fn split_lanes(&self, keep_title: bool) -> Vec<(String, Diagram)> {
+        let mut names = Vec::<String>::new();
+        let mut result = Vec::<(String, Diagram)>::new();
+        for num in 0..self.lane_count() {
+            let mut lane = match self.split_lane(num) {
+                Some(lane) => lane,
+                None => continue,
+            };
+            if !keep_title {
+                lane.set_title(None);
+                lane.set_subtitle(None);
+            }
+            let base = file_name(&self.lanes[num].signal.name).unwrap_or_else(|| format!("lane-{}", num + 1));
+            let mut name = base.clone();
+            let mut count = 1;
+            while names.contains(&name) {
+                count += 1;
+                name = format!("{}-{}", base, count);
+            }
+            names.push(name.clone());
+            result.push((name, lane));
+        }
+        result
+    }
+
+    /// Get a reference to the diagram's lane groups.
+    /// Outer groups come before the groups nested inside them.
+    pub fn groups(&self) -> &[LaneGroup] {
+        self.groups.as_ref()
+    }
+
+    /// Get a reference to the diagram's edges.
+    pub fn edges(&self) -> &[Edge] {
+        self.edges.as_ref()
+    }
+
+    /// Get a reference to the regions shaded across all lanes.
+    pub fn regions(&self) -> &[Region] {
+        self.regions.as_ref()
+    }
+
+    /// Find the point with the given name and return its lane index and position.
+    pub fn find_point(&self, name: &str) -> Option<(usize, f64)> {
+        self.lanes.iter().enumerate().find_map(|(idx, lane)| {
+            lane.points.iter().find(|p| p.name == name).map(|p| (idx, p.position))
+        })
+    }
+
+    /// Get the number of groups containing the group with the given index.
+    pub fn group_depth(&self, idx: usize) -> usize {
+        self.groups[..idx].iter().filter(|outer| outer.contains(&self.groups[idx])).count()
+    }
+
     /// Get a reference to the diagram's xaxis.
     pub fn xaxis(&self) -> Option<&String> {
         self.xaxis.as_ref()
     }
+
+    /// Get the diagram's theme.
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    /// Get the diagram's time axis.
+    pub fn time_axis(&self) -> Option<TimeAxis> {
+        self.time_axis
+    }
+
+    /// Get the duration of one period in seconds.
+    pub fn timebase(&self) -> Option<f64> {
+        self.timebase
+    }
+
+    /// Get a reference to the diagram's style.
+    pub fn style(&self) -> Option<&Style> {
+        self.style.as_ref()
+    }
+}
+
+
+/// A named span of consecutive lanes.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaneGroup {
+    pub name: String,
+    /// Index of the first lane.
+    pub start: usize,
+    /// Number of lanes in the group.
+    pub len: usize,
 }
 
+impl LaneGroup {
+    /// Index after the last lane.
+    pub fn end(&self) -> usize {
+        self.start + self.len
+    }
 
-#[derive(Debug, Default, Clone)]
+    /// Check if the other group lies inside this group.
+    pub fn contains(&self, other: &LaneGroup) -> bool {
+        self.start <= other.start && other.end() <= self.end()
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Lane {
     pub signal: Signal,
     pub markers: Vec<Marker>,
     pub labels: Vec<Label>,
+    /// Named points on the wave, which can be connected by the diagram's edges.
+    pub points: Vec<Point>,
+    pub dimensions: Vec<Dimension>,
+    pub regions: Vec<Region>,
 }
 
 impl Lane {
@@ -124,6 +467,45 @@ impl Lane {
         self
     }
 
+    /// Add a named point to the lane
+    /// Supports builder pattern
+    pub fn add_point(mut self, point: Point) -> Self {
+        self.points.push(point);
+        self
+    }
+
+    /// Append a named point to the lane
+    pub fn append_point(&mut self, point: Point) -> &mut Self {
+        self.points.push(point);
+        self
+    }
+
+    /// Add a dimension line to the lane
+    /// Supports builder pattern
+    pub fn add_dimension(mut self, dimension: Dimension) -> Self {
+        self.dimensions.push(dimension);
+        self
+    }
+
+    /// Append a dimension line to the lane
+    pub fn append_dimension(&mut self, dimension: Dimension) -> &mut Self {
+        self.dimensions.push(dimension);
+        self
+    }
+
+    /// Add a shaded region to the lane
+    /// Supports builder pattern
+    pub fn add_region(mut self, region: Region) -> Self {
+        self.regions.push(region);
+        self
+    }
+
+    /// Append a shaded region to the lane
+    pub fn append_region(&mut self, region: Region) -> &mut Self {
+        self.regions.push(region);
+        self
+    }
+
     /// Append maker to the lane
     pub fn append_marker(&mut self, marker: Marker) -> &mut Self {
         self.markers.push(marker);
@@ -164,3 +546,11 @@ impl Lane {
 
 }
 
+// Turn a signal name into a file name, keeping letters, digits, '-' and '_'.
+fn file_name(name: &str) -> Option<String> {
+    let name: String = name.trim().chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let name = name.trim_matches('_');
+    (!name.is_empty()).then(|| name.to_string())
+}
@@ -0,0 +1,63 @@
+// model/dimension.rs
+//
+// Dimension lines measuring the time between two positions of a lane, like setup and hold times in datasheets.
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+
+use super::utils::Color;
+
+/// Side of the wave a dimension line is placed on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    /// The side with less dimension lines, preferring above.
+    #[default]
+    Auto,
+    Above,
+    Below,
+}
+
+/// A horizontal line with arrows between two times and a label in its middle.
+/// Overlapping dimension lines on the same side are stacked.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Dimension {
+    /// Start time in periods.
+    pub start: f64,
+    /// End time in periods.
+    pub end: f64,
+    /// Text in the middle of the line, the measured duration is shown if empty.
+    pub label: String,
+    pub side: Side,
+    pub color: Color,
+}
+
+impl Dimension {
+    pub fn new(start: f64, end: f64) -> Self { Self { start, end, ..Default::default() } }
+
+    /// Set the text in the middle of the line.
+    /// Supports builder pattern
+    pub fn label<T: Into<String>>(mut self, label: T) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    /// Set the side of the wave the line is placed on.
+    /// Supports builder pattern
+    pub fn side(mut self, side: Side) -> Self {
+        self.side = side;
+        self
+    }
+
+    /// Coloring the line and its label
+    /// Supports builder pattern
+    pub fn color_with(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Get the measured duration in periods.
+    pub fn duration(&self) -> f64 {
+        (self.end - self.start).abs()
+    }
+}
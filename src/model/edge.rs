@@ -0,0 +1,72 @@
+// model/edge.rs
+//
+// Named points on the waves and the arrows drawn between them.
+use serde::{Serialize, Deserialize};
+
+/// A named point on a lane's wave, which can be connected by edges.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Point {
+    pub name: String,
+    /// Time in periods.
+    pub position: f64,
+}
+
+impl Point {
+    pub fn new<T: Into<String>>(name: T, position: f64) -> Self { Self { name: name.into(), position } }
+}
+
+/// How the line of an edge runs between its points.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EdgeLine {
+    #[default]
+    Straight,
+    /// A curve leaving and entering the points horizontally.
+    Spline,
+}
+
+/// A line between two named points, e.g. to annotate a causality or a propagation delay.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Edge {
+    /// Name of the point the edge starts at.
+    pub from: String,
+    /// Name of the point the edge ends at.
+    pub to: String,
+    pub line: EdgeLine,
+    /// Draw an arrow head at the start.
+    pub start_arrow: bool,
+    /// Draw an arrow head at the end.
+    pub end_arrow: bool,
+    /// Text placed in the middle of the edge.
+    pub label: String,
+}
+
+impl Edge {
+    /// An arrow pointing from one point to another.
+    pub fn new<T: Into<String>>(from: T, to: T) -> Self {
+        Self { from: from.into(), to: to.into(), end_arrow: true, ..Default::default() }
+    }
+
+    /// Draw the edge as a curve.
+    /// Supports builder pattern
+    pub fn spline(mut self) -> Self {
+        self.line = EdgeLine::Spline;
+        self
+    }
+
+    /// Set the arrow heads at the start and the end.
+    /// Supports builder pattern
+    pub fn arrows(mut self, start: bool, end: bool) -> Self {
+        self.start_arrow = start;
+        self.end_arrow = end;
+        self
+    }
+
+    /// Set the text placed in the middle of the edge.
+    /// Supports builder pattern
+    pub fn label<T: Into<String>>(mut self, label: T) -> Self {
+        self.label = label.into();
+        self
+    }
+}
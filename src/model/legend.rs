@@ -0,0 +1,86 @@
+// model/legend.rs
+//
+// Legend explaining the colors and line styles used in a diagram.
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+
+use super::utils::Color;
+
+/// Where the legend is placed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LegendPosition {
+    /// A column right of the waves.
+    #[default]
+    Right,
+    /// A row below the diagram.
+    Bottom,
+}
+
+/// How the swatch of a legend entry is drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Swatch {
+    #[default]
+    Line,
+    Dashed,
+    /// A shaded box like a region.
+    Area,
+}
+
+/// A swatch with the description of its meaning.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct LegendEntry {
+    pub color: Color,
+    pub swatch: Swatch,
+    pub description: String,
+}
+
+impl LegendEntry {
+    pub fn new<T: Into<String>>(color: Color, swatch: Swatch, description: T) -> Self {
+        Self { color, swatch, description: description.into() }
+    }
+}
+
+/// List of swatches with descriptions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Legend {
+    pub position: LegendPosition,
+    /// Entries shown before the collected ones.
+    pub entries: Vec<LegendEntry>,
+    /// Collect entries from the signals and markers which have a description.
+    pub auto: bool,
+}
+
+impl Default for Legend {
+    fn default() -> Self {
+        Self { position: LegendPosition::Right, entries: Vec::new(), auto: true }
+    }
+}
+
+impl Legend {
+    pub fn new() -> Self { Self::default() }
+
+    /// Set where the legend is placed.
+    /// Supports builder pattern
+    pub fn position(mut self, position: LegendPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Add an entry to the legend.
+    /// Supports builder pattern
+    pub fn entry(mut self, entry: LegendEntry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Enable or disable collecting the entries from the signals and markers.
+    /// Supports builder pattern
+    pub fn auto(mut self, auto: bool) -> Self {
+        self.auto = auto;
+        self
+    }
+}
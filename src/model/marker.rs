@@ -1,5 +1,8 @@
 use std::fmt::{Debug, Display};
 
+use schemars::{JsonSchema, gen::SchemaGenerator, schema::{InstanceType, Schema, SchemaObject}};
+use serde::{Serialize, Deserialize};
+
 use super::utils::Color;
 
 // todo!() rename Line to Marker and trait Marker to Positionable
@@ -32,22 +35,32 @@ impl Positionable for Label {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Marker {
     pub position: f64,
     pub dashed: bool,
     pub thickness: f64,
     pub color: Color,
+    /// Meaning of the marker's style, which is listed in the legend.
+    pub description: String,
 }
 
 impl Default for Marker {
     fn default() -> Self {
-        Self { position: 0.0, dashed: true, thickness: 1.0, color: Color::Lightgray }
+        Self { position: 0.0, dashed: true, thickness: 1.0, color: Color::Lightgray, description: String::new() }
     }
 }
 
 impl Marker {
-    pub fn new(position: f64, dashed: bool, thickness: f64, color: Color) -> Self { Self { position, dashed, thickness, color } }
+    pub fn new(position: f64, dashed: bool, thickness: f64, color: Color) -> Self { Self { position, dashed, thickness, color, description: String::new() } }
+
+    /// Describe the meaning of the marker's style for the legend.
+    /// Supports builder pattern.
+    pub fn describe<T: Into<String>>(mut self, description: T) -> Self {
+        self.description = description.into();
+        self
+    }
     
     /// Places the Line at a given wave position
     /// Supports builder pattern.
@@ -60,7 +73,8 @@ impl Marker {
 
 // Label 
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Label{
     pub text: String,
     pub position: f64,
@@ -120,21 +134,16 @@ impl Label {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub enum TextSize {
     Small,
     Smaller,
+    #[default]
     Normal,
     Larger,
     Large,
 }
 
-impl Default for TextSize {
-    fn default() -> Self {
-        Self::Normal
-    }
-}
-
 impl Display for TextSize {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -147,19 +156,32 @@ impl Display for TextSize {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TextAnchor {
+    #[serde(alias = "left")]
     Start,
+    #[serde(alias = "right")]
     End,
+    #[default]
+    #[serde(alias = "center", alias = "centre")]
     Middle,
 }
 
-impl Default for TextAnchor {
-    fn default() -> Self {
-        Self::Middle
+// The aliases are listed as well, a derived schema would only know the variant names.
+impl JsonSchema for TextAnchor {
+    fn schema_name() -> String {
+        "TextAnchor".to_string()
     }
-}
 
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(["start", "middle", "end", "left", "center", "centre", "right"].into_iter().map(Into::into).collect()),
+            ..Default::default()
+        }.into()
+    }
+}
 
 impl Display for TextAnchor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
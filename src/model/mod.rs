@@ -2,6 +2,22 @@ pub mod signal;
 pub mod utils;
 pub mod marker;
 pub mod diagram;
+pub mod axis;
+pub mod style;
+pub mod theme;
+pub mod edge;
+pub mod dimension;
+pub mod region;
+pub mod caption;
+pub mod legend;
 
 pub use signal::Signal;
 pub use diagram::*;
+pub use style::Style;
+pub use theme::Theme;
+pub use axis::TimeAxis;
+pub use edge::{Edge, Point};
+pub use dimension::Dimension;
+pub use region::Region;
+pub use caption::Caption;
+pub use legend::Legend;
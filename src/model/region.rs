@@ -0,0 +1,53 @@
+// model/region.rs
+//
+// Shaded time windows, e.g. to highlight a setup window or a single bit.
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+
+use super::utils::Color;
+
+/// A shaded time window drawn behind the waves of a lane or of all lanes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Region {
+    /// Start time in periods.
+    pub start: f64,
+    /// End time in periods.
+    pub end: f64,
+    pub color: Color,
+    /// Opacity of the shading from 0 to 1.
+    pub opacity: f64,
+    /// Text placed at the top of the region.
+    pub label: String,
+}
+
+impl Default for Region {
+    fn default() -> Self {
+        Self { start: 0.0, end: 0.0, color: Color::Yellow, opacity: 0.25, label: String::new() }
+    }
+}
+
+impl Region {
+    pub fn new(start: f64, end: f64) -> Self { Self { start, end, ..Default::default() } }
+
+    /// Coloring the region
+    /// Supports builder pattern
+    pub fn color_with(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Set the opacity of the shading.
+    /// Supports builder pattern
+    pub fn opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Set the text placed at the top of the region.
+    /// Supports builder pattern
+    pub fn label<T: Into<String>>(mut self, label: T) -> Self {
+        self.label = label.into();
+        self
+    }
+}
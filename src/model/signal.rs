@@ -1,10 +1,14 @@
 // siggi/model/signal.rs
 
+use std::fmt::Display;
+
+use serde::{Serialize, Deserialize};
 
 use super::{utils::Color};
 
 
-#[derive(Debug,Clone, PartialEq)]
+#[derive(Debug,Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Signal {
     pub name: String,  // todo!() change to Option<String>
     pub wave: Wave,
@@ -12,6 +16,8 @@ pub struct Signal {
     pub period: f64,    // Period len  -> default = 1.0
     pub color: Color,
     pub y_axis: (String, String),
+    /// Meaning of the signal's color, which is listed in the legend.
+    pub description: String,
 }
 
 impl Eq for Signal {}
@@ -19,7 +25,7 @@ impl Eq for Signal {}
 impl PartialOrd for Signal {
     // Order the signals by their wave length.
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.len().partial_cmp(&other.len()) 
+        Some(self.cmp(other))
     }
 }
 
@@ -29,7 +35,9 @@ impl Ord for Signal {
     }
 }
 
-#[derive(Debug,Clone, Default)]
+// Waves are (de)serialized in their string form, e.g. "hlud".
+#[derive(Debug,Clone, Default, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub struct Wave {
     pub levels: Vec<Level>
 }
@@ -40,6 +48,25 @@ impl Wave {
     pub fn len(&self) -> usize {
         self.levels.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+}
+
+impl Display for Wave {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for level in self.levels.iter() {
+            write!(f, "{}", level)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Wave> for String {
+    fn from(wave: Wave) -> Self {
+        wave.to_string()
+    }
 }
 
 impl PartialEq for Wave {
@@ -54,7 +81,7 @@ impl Eq for Wave {}
 impl PartialOrd for Wave {
     // Order the waves by length.
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.levels.len().partial_cmp(&other.levels.len())
+        Some(self.cmp(other))
     }
 }
 
@@ -73,6 +100,18 @@ pub enum Level {
     Down,
 }
 
+impl Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Level::Low => write!(f, "l"),
+            Level::High => write!(f, "h"),
+            Level::Idle => write!(f, "i"),
+            Level::Up => write!(f, "u"),
+            Level::Down => write!(f, "d"),
+        }
+    }
+}
+
 impl Default for Signal {
     fn default() -> Self {
         Self { 
@@ -81,7 +120,8 @@ impl Default for Signal {
             phase: 0.0, 
             period: 1.0, 
             color: Default::default(), 
-            y_axis: (String::from("H"),String::from("L")) }
+            y_axis: (String::from("H"),String::from("L")),
+            description: String::new() }
     }
 }
 
@@ -111,6 +151,13 @@ impl Signal {
         self
     }
 
+    /// Describe the meaning of the signal's color for the legend.
+    /// Supports builder pattern
+    pub fn describe<T>(mut self, description: T) -> Self where T: Into<String> {
+        self.description = description.into();
+        self
+    }
+
     pub fn label_yaxis_with(mut self, yaxis: (String,String)) -> Self {
         self.y_axis = yaxis;
         self
@@ -144,6 +191,55 @@ impl Signal {
     pub fn len(&self) -> u32 {
         (self.wave.len() as f64 * self.period).ceil() as u32  // todo!() return an f64 and find a way to compare those. 
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.wave.is_empty()
+    }
+
+    /// Get the time (in periods) at which the wave ends, including the phase.
+    pub fn end(&self) -> f64 {
+        self.phase + self.wave.len() as f64 * self.period
+    }
+
+    /// Get the times (in periods) at which the signal changes and the level it changes to.
+    /// Up and Down get resolved into their low and high halves, so only Low, High and Idle are returned.
+    pub fn transitions(&self) -> Vec<(f64, Level)> {
+        let first = match self.wave.levels.first() {
+            Some(Level::High) | Some(Level::Down) => Level::High,
+            Some(Level::Idle) => Level::Idle,
+            _ => Level::Low,
+        };
+        let mut result = vec![(0.0, first)];
+        let mut push = |time: f64, level: Level| {
+            if result.last().map(|(_, last)| *last) != Some(level) {
+                result.push((time, level));
+            }
+        };
+
+        for (num, level) in self.wave.levels.iter().enumerate() {
+            let start = self.phase + num as f64 * self.period;
+            match level {
+                Level::Up => {
+                    push(start, Level::Low);
+                    push(start + self.period / 2.0, Level::High);
+                }
+                Level::Down => {
+                    push(start, Level::High);
+                    push(start + self.period / 2.0, Level::Low);
+                }
+                level => push(start, *level),
+            }
+        }
+        result
+    }
+
+    /// Get the resolved level (Low, High or Idle) at the given time in periods.
+    pub fn level_at(&self, time: f64) -> Level {
+        self.transitions().iter()
+            .take_while(|(start, _)| *start <= time)
+            .last()
+            .map_or(Level::Low, |(_, level)| *level)
+    }
 }
 
 // Signal Generators
@@ -0,0 +1,149 @@
+// model/style.rs
+//
+// Sizes, strokes and fonts used to compose a diagram.
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+
+/// Names of the predefined styles, see `Style::preset`.
+pub const PRESETS: [&str; 4] = ["default", "compact", "print", "presentation"];
+
+/// Where vertical grid lines are drawn at the periods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Grid {
+    #[default]
+    None,
+    /// Separate lines across the waves of each lane.
+    Lanes,
+    /// Lines across the whole diagram.
+    Diagram,
+}
+
+/// Describes how a diagram is drawn. All sizes are given in pixels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Style {
+    /// Space around the diagram.
+    pub padding: f64,
+    /// Space reserved for the title.
+    pub header_height: f64,
+    /// Width of one period.
+    pub period_width: f64,
+    /// Distance between the low and the high level of the waves.
+    pub wave_height: f64,
+    /// Space above and below the waves of each lane.
+    pub wave_padding: f64,
+    /// Minimum width of the column left of the waves, which holds the signal names and y-axis labels.
+    /// The column grows to fit longer names.
+    pub name_width: f64,
+    /// Width of each nesting level of lane groups left of the names.
+    pub group_width: f64,
+    pub wave_stroke_width: f64,
+    /// Stroke width of the dashed level lines.
+    pub guide_stroke_width: f64,
+    /// Dash pattern of the level lines.
+    pub guide_dash: String,
+    /// Dash pattern of dashed markers.
+    pub marker_dash: String,
+    /// Vertical lines at every period.
+    pub grid: Grid,
+    /// Draw grid lines at half periods as well.
+    pub grid_half_periods: bool,
+    /// Stroke width of the grid lines.
+    pub grid_stroke_width: f64,
+    /// Stroke width of the x-axis arrow.
+    pub axis_stroke_width: f64,
+    pub font_family: String,
+    /// Factor applied to all font sizes.
+    pub font_scale: f64,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            padding: 30.0,
+            header_height: 25.0,
+            period_width: 50.0,
+            wave_height: 50.0,
+            wave_padding: 25.0,
+            name_width: 75.0,
+            group_width: 20.0,
+            wave_stroke_width: 3.0,
+            guide_stroke_width: 0.5,
+            guide_dash: "10 6".into(),
+            marker_dash: "3 3".into(),
+            grid: Grid::None,
+            grid_half_periods: false,
+            grid_stroke_width: 0.5,
+            axis_stroke_width: 1.0,
+            font_family: "Segoe Print".into(),
+            font_scale: 1.0,
+        }
+    }
+}
+
+impl Style {
+    /// Small sizes for diagrams with many lanes or periods.
+    pub fn compact() -> Self {
+        Self {
+            padding: 20.0,
+            header_height: 15.0,
+            period_width: 25.0,
+            wave_height: 20.0,
+            wave_padding: 10.0,
+            name_width: 70.0,
+            group_width: 16.0,
+            wave_stroke_width: 2.0,
+            guide_dash: "4 3".into(),
+            font_scale: 0.85,
+            ..Default::default()
+        }
+    }
+
+    /// Thin lines and a common font, suited for documents.
+    pub fn print() -> Self {
+        Self {
+            wave_stroke_width: 1.5,
+            guide_stroke_width: 0.4,
+            guide_dash: "2 2".into(),
+            marker_dash: "2 2".into(),
+            axis_stroke_width: 0.75,
+            font_family: "Helvetica, Arial, sans-serif".into(),
+            ..Default::default()
+        }
+    }
+
+    /// Big sizes and bold lines, readable on slides.
+    pub fn presentation() -> Self {
+        Self {
+            padding: 40.0,
+            header_height: 40.0,
+            period_width: 60.0,
+            wave_height: 60.0,
+            wave_padding: 30.0,
+            name_width: 110.0,
+            group_width: 28.0,
+            wave_stroke_width: 4.0,
+            guide_stroke_width: 1.0,
+            axis_stroke_width: 2.0,
+            font_scale: 1.5,
+            ..Default::default()
+        }
+    }
+
+    /// Get a predefined style by name, see `PRESETS`.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "default" => Some(Self::default()),
+            "compact" => Some(Self::compact()),
+            "print" => Some(Self::print()),
+            "presentation" => Some(Self::presentation()),
+            _ => None,
+        }
+    }
+
+    /// Height of a single lane.
+    pub fn lane_height(&self) -> f64 {
+        self.wave_height + 2.0 * self.wave_padding
+    }
+}
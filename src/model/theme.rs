@@ -0,0 +1,79 @@
+// model/theme.rs
+//
+// Colors used for the parts of a diagram which don't have an own color.
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+
+use super::utils::Color;
+
+/// Names of the predefined themes, see `Theme::preset`.
+pub const THEMES: [&str; 2] = ["light", "dark"];
+
+/// Remaps the default colors, so they stay readable on the diagram's background.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Theme {
+    /// Background used by the theme, if the diagram doesn't set one.
+    pub background: Color,
+    /// Replaces black texts like the title, signal names and labels.
+    pub text: Color,
+    /// Replaces black signals.
+    pub signal: Color,
+    /// Replaces the lightgray level lines, axis, markers and y-axis labels.
+    pub guide: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl Theme {
+    /// Black on white, which keeps all colors as they are.
+    pub fn light() -> Self {
+        Self { background: Color::White, text: Color::Black, signal: Color::Black, guide: Color::Lightgray }
+    }
+
+    /// Light texts and signals on a dark background.
+    pub fn dark() -> Self {
+        Self {
+            background: Color::Darkgray,
+            text: Color::Custom((0xEE, 0xEE, 0xEE)),
+            signal: Color::White,
+            guide: Color::Custom((0xA0, 0xAE, 0xB9)),
+        }
+    }
+
+    /// Get a predefined theme by name, see `THEMES`.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "light" => Some(Self::light()),
+            "dark" => Some(Self::dark()),
+            _ => None,
+        }
+    }
+
+    /// Get the color of a text.
+    pub fn text_color(&self, color: Color) -> Color {
+        self.remap(color, self.text)
+    }
+
+    /// Get the color of a signal's wave.
+    pub fn signal_color(&self, color: Color) -> Color {
+        self.remap(color, self.signal)
+    }
+
+    /// Get the color of guide lines.
+    pub fn guide_color(&self, color: Color) -> Color {
+        self.remap(color, self.guide)
+    }
+
+    fn remap(&self, color: Color, black: Color) -> Color {
+        match color {
+            Color::Black => black,
+            Color::Lightgray => self.guide,
+            _ => color,
+        }
+    }
+}
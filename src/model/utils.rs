@@ -1,36 +1,40 @@
 use std::fmt::Display;
 
+use schemars::JsonSchema;
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
 pub enum Color { 
     Yellow,
     Blue, 
     Red,
     White,
+    #[default]
     Black,
     Darkgray,
     Lightgray,
     Custom((u8,u8,u8)),
 }
 
-impl Default for Color {
-    fn default() -> Self {
-        Color::Black
+impl Color {
+    /// Get the color's red, green and blue components.
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        match self {
+            Color::Yellow => (0xFF, 0xC3, 0x00),
+            Color::Blue => (0x0D, 0x84, 0xF0),
+            Color::Red => (0xFF, 0x57, 0x33),
+            Color::White => (0xFF, 0xFF, 0xFF),
+            Color::Black => (0x00, 0x00, 0x00),
+            Color::Darkgray => (0x1c, 0x28, 0x33),
+            Color::Lightgray => (0x74, 0x83, 0x8f),
+            Color::Custom(rgb) => *rgb,
+        }
     }
 }
 
 impl Display for Color {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Color::Yellow => write!(f,"#FFC300"),
-            Color::Blue => write!(f,"#0D84F0"),
-            Color::Red => write!(f,"#FF5733"),
-            Color::White => write!(f,"#FFFFFF"),
-            Color::Black => write!(f,"#000000"),
-            Color::Darkgray => write!(f,"#1c2833"),
-            Color::Lightgray => write!(f,"#74838f"),
-            Color::Custom((r,g,b)) => write!(f,"#{:#2x}{:#2x}{:#2x}",r,g,b),
-        }
+        let (r, g, b) = self.rgb();
+        write!(f,"#{:02X}{:02X}{:02X}",r,g,b)
     }
 }
\ No newline at end of file
@@ -1,15 +1,20 @@
 // parse.rs 
 pub mod error;
 
-use serde::{Deserialize, Deserializer, de};
+use schemars::{JsonSchema, schema_for};
+use serde::{Serialize, Deserialize, Deserializer, de::{self, DeserializeOwned}};
 use serde_json::Value;
 
 use std::str::FromStr;
 
-use crate::model::{signal::{Wave, Level, Clock, SignalGenerator}, Signal, Diagram, Lane, utils::Color, marker::{Marker, Label}};
+use crate::model::{signal::{Wave, Level, Clock, SignalGenerator}, Signal, Diagram, Lane, utils::Color, marker::{Marker, Label}, Style, Theme, TimeAxis, Edge, Point, edge::EdgeLine, Dimension, Region, Caption, Legend};
 use self::error::{ParseWaveError, ParseError};
 
 
+// Version of the saved model format, see `to_json_string`.
+// Increase it on changes which can't be loaded by older versions.
+pub const MODEL_VERSION: u32 = 1;
+
 fn default_to_1() -> f64 {
     1.0
 }
@@ -18,50 +23,242 @@ fn default_yaxis() -> YAxis {
     YAxis { top: "H".into(), bottom: "L".into() }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedDiagram {
+    version: u32,
+    diagram: Diagram,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(title = "siggi diagram")]
 struct JsonData {
-    signals: Vec<JsonSignal>,
+    signals: Vec<JsonEntry>,
     config: Option<JsonConfig>,
+    /// Arrows between the points named by the signals' nodes, e.g. "a->b t_pd".
+    /// The shape between the names is one of -, ->, <-, <->, ~, ~>, <~ or <~> (~ draws a curve), followed by an optional label.
+    #[serde(default)]
+    #[schemars(with = "Vec<JsonEdge>")]
+    edges: Vec<String>,
+    /// Time windows shaded across all lanes.
+    #[serde(default)]
+    regions: Vec<Region>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+// Describes the edge format for the json schema, see `Edge::from_str` for the parsing.
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+struct JsonEdge(#[schemars(regex(pattern = r"^[^\s.<>~-](-|->|<-|<->|~|~>|<~|<~>)[^\s.<>~-](\s.*)?$"))] String);
+
+#[derive(Debug, Deserialize, Default, JsonSchema)]
 struct  JsonConfig {
     title: Option<String>,
-    _background: Option<String>,
+    /// Smaller text below the title.
+    subtitle: Option<String>,
+    /// Text below the diagram, lines are separated by "\n". An object can also set the alignment and a figure number.
+    #[serde(default, deserialize_with = "de_caption")]
+    #[schemars(with = "Option<JsonCaption>")]
+    caption: Option<Caption>,
+    /// Source or author line below the caption.
+    source: Option<String>,
+    /// Show a legend with the signals and markers which have a description.
+    #[serde(default, deserialize_with = "de_legend")]
+    #[schemars(with = "Option<JsonLegend>")]
+    legend: Option<Legend>,
+    /// Overrides the background of the theme.
+    background: Option<Color>,
     xaxis: Option<String>,
+    /// One of the predefined styles (default, compact, print or presentation) or an object overriding single values.
+    #[serde(default, deserialize_with = "de_preset")]
+    #[schemars(with = "Option<JsonPreset<Style>>")]
+    style: Option<Style>,
+    /// One of the predefined themes (light or dark) or an object overriding single colors.
+    #[serde(default, deserialize_with = "de_preset")]
+    #[schemars(with = "Option<JsonPreset<Theme>>")]
+    theme: Option<Theme>,
+    /// Duration of one period, e.g. "10ns", "2.5 us" or "1ms".
+    #[schemars(regex(pattern = r"^\s*[0-9.eE+-]+\s*(s|ms|us|µs|ns|ps|fs)\s*$"))]
+    timebase: Option<String>,
+    /// Draw a time ruler below the lanes.
+    #[serde(default, deserialize_with = "de_time_axis")]
+    #[schemars(with = "Option<JsonTimeAxis>")]
+    time_axis: Option<TimeAxis>,
     //_show_ticks: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum JsonEntry {
+    /// A named group of signals, drawn with a bracket left of the signal names. Groups can be nested.
+    Group {
+        group: String,
+        signals: Vec<JsonEntry>,
+    },
+    Signal(Box<JsonSignal>),
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 struct JsonSignal {
     name: String,
+    /// One character per period: l(ow), h(igh), u(p), d(own) or i(dle).
+    #[schemars(regex(pattern = r"^[lhudiLHUDI]*$"))]
     wave: String,
+    /// Shifts the wave by the given number of periods.
     #[serde(default)]
     phase: f64,
+    /// Scales the period duration of the wave.
     #[serde(default = "default_to_1")]
     period: f64,
     #[serde(default)]
     color: Color,
+    /// Meaning of the signal's color, which is listed in the legend.
+    #[serde(default)]
+    description: String,
     #[serde(default = "default_yaxis")]
     yaxis: YAxis,
     #[serde(default, deserialize_with = "de_markers")]
+    #[schemars(with = "Vec<JsonMarker>")]
     markers: Vec<Marker>,
+    /// One character per period naming a point at the start of the period, which can be connected by edges. "." names no point.
+    #[serde(default)]
+    node: String,
+    /// Time windows shaded behind the wave.
+    #[serde(default)]
+    regions: Vec<Region>,
+    /// Dimension lines measuring the time between two positions.
+    #[serde(default)]
+    dimensions: Vec<Dimension>,
+    /// Labels placed at the start of each period.
     #[serde(default)]
     ticks: Vec<String>,
+    /// Labels placed in the middle of each period.
     #[serde(default)]
     tocks: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 struct YAxis {
     top: String,
     bottom: String,
 }
 
+// Describes the accepted marker formats for the json schema, see `de_markers` for the parsing.
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum JsonMarker {
+    /// A default marker at the given position.
+    Position(f64),
+    /// Styled markers at all given positions.
+    Styled {
+        at: Vec<f64>,
+        color: Option<Color>,
+        dashed: Option<bool>,
+        thickness: Option<f64>,
+        /// Meaning of the marker's style, which is listed in the legend.
+        description: Option<String>,
+    },
+}
+
+// Describes the accepted style and theme formats for the json schema, see `de_preset` for the parsing.
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum JsonPreset<T> {
+    /// Name of a predefined value.
+    Preset(String),
+    /// Values overriding the ones given by preset (or the default).
+    Custom {
+        preset: Option<String>,
+        #[serde(flatten)]
+        values: T,
+    },
+}
+
+fn de_preset<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr + Default + Serialize + DeserializeOwned,
+{
+    let preset = |name: &str| name.parse::<T>().map_err(|_| de::Error::custom(format!("unknown preset {}", name)));
+
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        Value::String(name) => preset(&name).map(Some),
+        Value::Object(mut overrides) => {
+            let base = match overrides.remove("preset") {
+                Some(Value::String(name)) => preset(&name)?,
+                Some(_) => return Err(de::Error::custom("preset must be a string")),
+                None => T::default(),
+            };
+            // apply the given values on top of the preset.
+            let mut value = serde_json::to_value(base).map_err(de::Error::custom)?;
+            if let Value::Object(values) = &mut value {
+                values.extend(overrides);
+            }
+            serde_json::from_value(value).map(Some).map_err(de::Error::custom)
+        }
+        _ => Err(de::Error::custom("wrong type")),
+    }
+}
+
+// Describes the accepted time axis formats for the json schema, see `de_time_axis` for the parsing.
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum JsonTimeAxis {
+    /// Show a default time axis with a labeled tick per period.
+    Enabled(bool),
+    Custom(TimeAxis),
+}
+
+fn de_time_axis<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<TimeAxis>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null | Value::Bool(false) => Ok(None),
+        Value::Bool(true) => Ok(Some(TimeAxis::default())),
+        value => serde_json::from_value(value).map(Some).map_err(de::Error::custom),
+    }
+}
+
+// Describes the accepted caption formats for the json schema, see `de_caption` for the parsing.
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum JsonCaption {
+    /// A centered caption.
+    Text(String),
+    Custom(Caption),
+}
+
+fn de_caption<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Caption>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        Value::String(text) => Ok(Some(Caption::new(text))),
+        value => serde_json::from_value(value).map(Some).map_err(de::Error::custom),
+    }
+}
+
+// Describes the accepted legend formats for the json schema, see `de_legend` for the parsing.
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum JsonLegend {
+    /// Show a legend right of the waves.
+    Enabled(bool),
+    Custom(Legend),
+}
+
+fn de_legend<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Legend>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null | Value::Bool(false) => Ok(None),
+        Value::Bool(true) => Ok(Some(Legend::default())),
+        value => serde_json::from_value(value).map(Some).map_err(de::Error::custom),
+    }
+}
+
 fn de_markers<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Marker>, D::Error> {
     let mut markers = Vec::<Marker>::new();
 
-    for value in Value::deserialize(deserializer) {
+    if let Ok(value) = Value::deserialize(deserializer) {
         if let Ok(Value::Array(arr)) = Value::deserialize(value) {
             for val in arr {
                 match val {
@@ -83,10 +280,14 @@ fn de_markers<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Marker>,
                             serde_json::from_value(field.clone()).unwrap_or(1.0) 
                         } else { 1.0 };
                         
+                        let description : String = if let Some(field) = marker.get("description") { 
+                            serde_json::from_value(field.clone()).unwrap_or_default() 
+                        } else { String::new() };
+
                         if let Some(positions) = marker["at"].as_array() {
                             for pos in positions {
                                 if let Some(pos) = pos.as_f64() {
-                                    markers.push(Marker::new(pos, dashed, thickness, color));
+                                    markers.push(Marker::new(pos, dashed, thickness, color).describe(description.as_str()));
                                 }
                             }
                         }
@@ -103,19 +304,91 @@ fn de_markers<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Marker>,
 
 
 // parse diagram from json str
+// Accepts the input format described by `json_schema` as well as diagrams saved with `to_json_string`.
 pub fn from_json_str(json: &str) -> Result<Diagram,ParseError> {
 
-    let data : JsonData = serde_json::from_str(json)?;
-    
-    let mut diagram = Diagram::from(data.config.unwrap_or_default());
-
-    for json_signal in data.signals {
-        diagram.append(Lane::try_from(&json_signal)?);
+    let value : Value = serde_json::from_str(json)?;
+    if let Some(version) = value.get("version") {
+        let version = version.as_u64().ok_or(ParseError::ParseVersionError)?;
+        if version == 0 || version > MODEL_VERSION as u64 {
+            return Err(ParseError::ParseVersionError);
+        }
+        let data : SavedDiagram = serde_json::from_value(value)?;
+        // the groups are loaded as they are, so they must only span existing lanes.
+        let lane_count = data.diagram.lane_count();
+        if data.diagram.groups().iter().any(|group| group.start.checked_add(group.len).is_none_or(|end| end > lane_count)) {
+            return Err(ParseError::ParseDiagramError);
+        }
+        return Ok(data.diagram);
     }
+
+    let data : JsonData = serde_json::from_value(value)?;
     
+    let config = data.config.unwrap_or_default();
+    let timebase = config.timebase.as_deref().map(parse_duration).transpose()?;
+    let mut diagram = Diagram::from(config);
+    diagram.set_timebase(timebase);
+
+    append_entries(&mut diagram, &data.signals)?;
+    for edge in data.edges.iter() {
+        diagram.append_edge(edge.parse()?);
+    }
+    for region in data.regions.iter() {
+        diagram.append_region(region.clone());
+    }
+
     Ok(diagram)
 }
 
+// append the signals and groups recursively.
+fn append_entries(diagram: &mut Diagram, entries: &[JsonEntry]) -> Result<(), ParseError> {
+    for entry in entries {
+        match entry {
+            JsonEntry::Group { group, signals } => {
+                diagram.start_group(group.as_str());
+                append_entries(diagram, signals)?;
+                diagram.end_group();
+            }
+            JsonEntry::Signal(json_signal) => {
+                diagram.append(Lane::try_from(json_signal.as_ref())?);
+            }
+        }
+    }
+    Ok(())
+}
+
+// save the whole diagram model as versioned json, which can be loaded again with `from_json_str`.
+pub fn to_json_string(diagram: &Diagram) -> Result<String,ParseError> {
+    let data = SavedDiagram { version: MODEL_VERSION, diagram: diagram.clone() };
+    Ok(serde_json::to_string_pretty(&data)?)
+}
+
+// json schema describing the input format of `from_json_str`
+pub fn json_schema() -> String {
+    serde_json::to_string_pretty(&schema_for!(JsonData)).expect("Could not serialize the json schema")
+}
+
+// parse a duration like "10ns" or "2.5 us" into seconds.
+pub fn parse_duration(s: &str) -> Result<f64, ParseError> {
+    let s = s.trim();
+    // the unit is the trailing run of letters, so exponents like 1e3ns stay part of the value.
+    let (value, unit) = s.split_at(s.trim_end_matches(|c: char| c.is_alphabetic()).len());
+    let value: f64 = value.trim().parse().map_err(|_| ParseError::ParseTimebaseError)?;
+    let factor = match unit {
+        "s" => 1.0,
+        "ms" => 1e-3,
+        "us" | "µs" => 1e-6,
+        "ns" => 1e-9,
+        "ps" => 1e-12,
+        "fs" => 1e-15,
+        _ => return Err(ParseError::ParseTimebaseError),
+    };
+    if value <= 0.0 {
+        return Err(ParseError::ParseTimebaseError);
+    }
+    Ok(value * factor)
+}
+
 // parse diagram from args  -> not all features are supported
 pub fn from_args(title: String, dark: bool, clocks: Vec<String>, signals: Vec<String>) -> Result<Diagram,ParseError> {
     let mut diag = Diagram::new(Some(title)).dark(dark);
@@ -151,6 +424,58 @@ impl FromStr for Signal {
     }
 }
 
+impl FromStr for Style {
+    type Err = ParseError;
+
+    // Parse the name of a predefined style
+    fn from_str(s: &str) -> Result<Self,Self::Err> {
+        Style::preset(s).ok_or(ParseError::ParseStyleError)
+    }
+}
+
+impl FromStr for Theme {
+    type Err = ParseError;
+
+    // Parse the name of a predefined theme
+    fn from_str(s: &str) -> Result<Self,Self::Err> {
+        Theme::preset(s).ok_or(ParseError::ParseThemeError)
+    }
+}
+
+impl FromStr for Edge {
+    type Err = ParseError;
+
+    // Parse an edge like "a->b t_pd", the point names are single characters.
+    fn from_str(s: &str) -> Result<Self,Self::Err> {
+        let s = s.trim();
+        let (spec, label) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let chars: Vec<char> = spec.chars().collect();
+        // '.' marks a position without point in a signal's node string and the other characters draw the line.
+        let is_name = |c: &char| !".-<>~".contains(*c);
+        if chars.len() < 3 || !is_name(&chars[0]) || !is_name(&chars[chars.len() - 1]) {
+            return Err(ParseError::ParseEdgeError);
+        }
+        let shape: String = chars[1..chars.len() - 1].iter().collect();
+        let (line, start_arrow, end_arrow) = match shape.as_str() {
+            "-" => (EdgeLine::Straight, false, false),
+            "->" => (EdgeLine::Straight, false, true),
+            "<-" => (EdgeLine::Straight, true, false),
+            "<->" => (EdgeLine::Straight, true, true),
+            "~" => (EdgeLine::Spline, false, false),
+            "~>" => (EdgeLine::Spline, false, true),
+            "<~" => (EdgeLine::Spline, true, false),
+            "<~>" => (EdgeLine::Spline, true, true),
+            _ => return Err(ParseError::ParseEdgeError),
+        };
+        Ok(Edge {
+            from: chars[0].to_string(),
+            to: chars[chars.len() - 1].to_string(),
+            line, start_arrow, end_arrow,
+            label: label.trim().to_string(),
+        })
+    }
+}
+
 impl FromStr for Wave {
     type Err = ParseWaveError;
 
@@ -171,6 +496,14 @@ impl FromStr for Wave {
     }
 }
 
+impl TryFrom<String> for Wave {
+    type Error = ParseWaveError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 impl YAxis {
     pub fn to_tuple(&self) -> (String, String) {
         (self.top.clone(), self.bottom.clone())
@@ -185,19 +518,32 @@ impl TryFrom<&JsonSignal> for Signal {
             .shift(json_signal.phase)
             .scale(json_signal.period)
             .color_with(json_signal.color)
+            .describe(json_signal.description.as_str())
             .label_yaxis_with(json_signal.yaxis.to_tuple()))
     }
 } 
 
 impl From<JsonConfig> for Diagram {
     fn from(json_config: JsonConfig) -> Self {
-        if let Some(xaxis) = json_config.xaxis {
+        let mut diagram = if let Some(xaxis) = json_config.xaxis {
             Diagram::new(json_config.title).has_xaxis(&xaxis)
         } else {
             // Has no xaxis
             Diagram::new(json_config.title)
+        };
+        diagram.set_subtitle(json_config.subtitle);
+        diagram.set_caption(json_config.caption);
+        diagram.set_source(json_config.source);
+        diagram.set_legend(json_config.legend);
+        diagram.set_style(json_config.style);
+        if let Some(theme) = json_config.theme {
+            diagram.set_theme(theme);
+        }
+        if let Some(background) = json_config.background {
+            diagram.set_background(background);
         }
-        // background not supported yet
+        diagram.set_time_axis(json_config.time_axis);
+        diagram
     }
 }
 
@@ -209,7 +555,21 @@ impl TryFrom<&JsonSignal> for Lane {
         let mut lane = Lane::new(Signal::try_from(json_signal)?);
 
         for marker in json_signal.markers.iter() {
-            lane.append_marker(*marker);
+            lane.append_marker(marker.clone());
+        }
+
+        for region in json_signal.regions.iter() {
+            lane.append_region(region.clone());
+        }
+
+        for dimension in json_signal.dimensions.iter() {
+            lane.append_dimension(dimension.clone());
+        }
+
+        for (num,name) in json_signal.node.chars().enumerate() {
+            if name != '.' {
+                lane.append_point(Point::new(name, json_signal.phase + num as f64 * json_signal.period));
+            }
         }
 
         for (num,string) in json_signal.ticks.iter().enumerate() {
@@ -222,4 +582,119 @@ impl TryFrom<&JsonSignal> for Lane {
 
         Ok(lane)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::marker::TextAnchor;
+    use crate::compose::Compositor;
+
+    #[test]
+    fn durations_in_all_units() {
+        let cases = [("1s", 1.0), ("10ms", 1e-2), ("2.5 us", 2.5e-6), ("3µs", 3e-6), (" 10ns ", 1e-8), ("4ps", 4e-12), ("1e3fs", 1e-12)];
+        for (text, seconds) in cases {
+            let duration = parse_duration(text).unwrap();
+            assert!((duration - seconds).abs() <= seconds * 1e-12, "{} parsed as {}", text, duration);
+        }
+    }
+
+    #[test]
+    fn durations_are_rejected() {
+        for text in ["", "10", "ns", "10 hz", "10nss", "-1ns", "0ns", "1.2.3ns"] {
+            assert!(matches!(parse_duration(text), Err(ParseError::ParseTimebaseError)), "{:?} was accepted", text);
+        }
+    }
+
+    #[test]
+    fn edge_shapes() {
+        let cases = [
+            ("a-b", EdgeLine::Straight, false, false),
+            ("a->b", EdgeLine::Straight, false, true),
+            ("a<-b", EdgeLine::Straight, true, false),
+            ("a<->b", EdgeLine::Straight, true, true),
+            ("a~b", EdgeLine::Spline, false, false),
+            ("a~>b", EdgeLine::Spline, false, true),
+            ("a<~b", EdgeLine::Spline, true, false),
+            ("a<~>b", EdgeLine::Spline, true, true),
+        ];
+        for (text, line, start_arrow, end_arrow) in cases {
+            let edge: Edge = text.parse().unwrap();
+            assert_eq!(edge, Edge { from: "a".into(), to: "b".into(), line, start_arrow, end_arrow, label: String::new() }, "{}", text);
+        }
+    }
+
+    #[test]
+    fn edge_labels() {
+        let edge: Edge = "  a~>b  t_pd 5ns ".parse().unwrap();
+        assert_eq!((edge.from.as_str(), edge.to.as_str(), edge.label.as_str()), ("a", "b", "t_pd 5ns"));
+    }
+
+    #[test]
+    fn nodes_sit_on_the_wave_edges() {
+        let diag = from_json_str(r#"{"signals":[{"name":"A","wave":"lhlh","phase":0.5,"period":2,"node":".a.b"}]}"#).unwrap();
+        let edges: Vec<f64> = diag.lanes()[0].signal.transitions().iter().map(|t| t.0).collect();
+        assert_eq!(diag.find_point("a").map(|p| p.1), Some(edges[1]));
+        assert_eq!(diag.find_point("b").map(|p| p.1), Some(edges[3]));
+    }
+
+    #[test]
+    fn malformed_edges() {
+        for text in ["", "a", "ab", "a>b", "a-->b", "a<>b", "a~-b", "a=>b", "a->", "->b t"] {
+            assert!(matches!(text.parse::<Edge>(), Err(ParseError::ParseEdgeError)), "{:?} was accepted", text);
+        }
+    }
+
+    #[test]
+    fn saved_groups_must_span_existing_lanes() {
+        let saved = |groups: &str| format!(r#"{{"version":1,"diagram":{{"lanes":[{{"signal":{{"name":"A","wave":"lh"}}}}],"groups":[{}]}}}}"#, groups);
+        assert!(from_json_str(&saved(r#"{"name":"x","start":0,"len":1}"#)).is_ok());
+        for group in [r#"{"name":"x","start":3,"len":2}"#, r#"{"name":"x","start":0,"len":2}"#, r#"{"name":"x","start":1,"len":18446744073709551615}"#] {
+            assert!(matches!(from_json_str(&saved(group)), Err(ParseError::ParseDiagramError)), "{} was accepted", group);
+        }
+    }
+
+    #[test]
+    fn caption_alignments() {
+        let cases = [("left", TextAnchor::Start), ("start", TextAnchor::Start), ("center", TextAnchor::Middle), ("centre", TextAnchor::Middle), ("middle", TextAnchor::Middle), ("right", TextAnchor::End), ("end", TextAnchor::End)];
+        for (align, anchor) in cases {
+            let diag = from_json_str(&format!(r#"{{"signals":[],"config":{{"caption":{{"text":"x","align":"{}"}}}}}}"#, align)).unwrap();
+            assert_eq!(diag.caption().map(|caption| caption.align), Some(anchor), "{}", align);
+        }
+        assert_eq!(serde_json::to_string(&TextAnchor::Start).unwrap(), r#""start""#);
+        assert!(from_json_str(r#"{"signals":[],"config":{"caption":{"text":"x","align":"Start"}}}"#).is_err());
+    }
+
+    #[test]
+    fn saved_diagrams_are_loaded_unchanged() {
+        let diag = from_json_str(r#"{
+            "config":{"title":"Saved","subtitle":"all parts","caption":{"text":"Round trip","align":"left","number":2},"source":"siggi",
+                "legend":true,"style":{"preset":"presentation","period_width":30},"theme":{"preset":"dark","background":"Blue"},"timebase":"10ns","time_axis":true},
+            "signals":[
+                {"group":"Bus","signals":[
+                    {"name":"CLK","wave":"lhlhlhlh","description":"clock","node":".a...b"},
+                    {"name":"DATA","wave":"lluuhhdd","markers":[{"at":[2,5],"description":"sample"}],"regions":[{"start":2,"end":4,"label":"valid"}],
+                        "dimensions":[{"start":2,"end":6,"label":"t_hold"}],"ticks":["A","B"]}]},
+                {"name":"EN","wave":"lhhhhhhl","phase":0.5}],
+            "edges":["a~>b t_pd"],
+            "regions":[{"start":5,"end":7,"label":"burst"}]}"#).unwrap();
+        let saved = to_json_string(&diag).unwrap();
+        for part in ["\"Bus\"", "\"t_pd\"", "\"t_hold\"", "\"valid\"", "\"burst\"", "\"sample\"", "\"Round trip\""] {
+            assert!(saved.contains(part), "{} was not saved", part);
+        }
+        let loaded = from_json_str(&saved).unwrap();
+        assert_eq!(to_json_string(&loaded).unwrap(), saved);
+        assert_eq!(Compositor::default().compose(&loaded).to_string(), Compositor::default().compose(&diag).to_string());
+    }
+
+    #[test]
+    fn style_presets_with_overrides() {
+        let config = |config: &str| from_json_str(&format!(r#"{{"signals":[],"config":{}}}"#, config));
+        assert_eq!(config(r#"{"style":"print"}"#).unwrap().style(), Some(&Style::print()));
+        assert_eq!(config(r#"{"style":{"preset":"compact","period_width":40}}"#).unwrap().style(), Some(&Style { period_width: 40.0, ..Style::compact() }));
+        assert_eq!(config(r#"{"style":{"font_scale":2}}"#).unwrap().style(), Some(&Style { font_scale: 2.0, ..Style::default() }));
+        for style in [r#""huge""#, r#"{"preset":"huge"}"#, r#"{"preset":3}"#, "3"] {
+            assert!(config(&format!(r#"{{"style":{}}}"#, style)).is_err(), "{} was accepted", style);
+        }
+    }
+}
@@ -32,6 +32,12 @@ pub enum ParseError {
     ParseSignalError,
     ParseConfigError,
     ParseDiagramError,
+    ParseBookError,
+    ParseVersionError,
+    ParseStyleError,
+    ParseThemeError,
+    ParseTimebaseError,
+    ParseEdgeError,
     ParseJsonError(serde_json::Error)
 }
 
@@ -42,11 +48,19 @@ impl fmt::Display for ParseError {
             ParseError::ParseSignalError => write!(f," Error occured during signal parsing"),
             ParseError::ParseConfigError => write!(f," Error occured during config parsing"),
             ParseError::ParseDiagramError => write!(f," Error occured during diagram parsing"),
+            ParseError::ParseBookError => write!(f," Error occured during book parsing"),
+            ParseError::ParseVersionError => write!(f," Unsupported diagram version"),
+            ParseError::ParseStyleError => write!(f," Unknown style preset"),
+            ParseError::ParseThemeError => write!(f," Unknown theme"),
+            ParseError::ParseTimebaseError => write!(f," Error occured during timebase parsing"),
+            ParseError::ParseEdgeError => write!(f," Error occured during edge parsing"),
             ParseError::ParseJsonError(_) => write!(f," Error occured during json parsing"),
         }
     }
 }
 
+impl Error for ParseError {}
+
 impl From<serde_json::Error> for ParseError {
     fn from(err: serde_json::Error) -> Self {
         ParseError::ParseJsonError(err)